@@ -0,0 +1,221 @@
+//! Align and distribute operations for multiple selected entities
+//!
+//! These work with the world-space bounding boxes of the selected entities,
+//! as shown by their selection visuals, and move entities by changing the
+//! translation of their (local) `Transform`.
+
+use crate::crate_prelude::*;
+use crate::history::{EditorHistory, HistoryStep, TransformChange};
+use crate::selection::{Selection, SelectionVisualBounds};
+use crate::spatial::world_bounds;
+use crate::transform::world_to_local;
+
+/// The available align/distribute operations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrangeOp {
+    AlignLeft,
+    AlignRight,
+    AlignTop,
+    AlignBottom,
+    /// Align the centers along a vertical line
+    AlignCenterHorizontal,
+    /// Align the centers along a horizontal line
+    AlignCenterVertical,
+    /// Space the centers evenly, keeping the leftmost/rightmost in place
+    DistributeHorizontal,
+    /// Space the centers evenly, keeping the bottommost/topmost in place
+    DistributeVertical,
+    /// Make the horizontal gaps between the bounding boxes equal
+    MatchSpacingHorizontal,
+    /// Make the vertical gaps between the bounding boxes equal
+    MatchSpacingVertical,
+}
+
+impl ArrangeOp {
+    pub(crate) const ALL: [ArrangeOp; 10] = [
+        ArrangeOp::AlignLeft,
+        ArrangeOp::AlignRight,
+        ArrangeOp::AlignTop,
+        ArrangeOp::AlignBottom,
+        ArrangeOp::AlignCenterHorizontal,
+        ArrangeOp::AlignCenterVertical,
+        ArrangeOp::DistributeHorizontal,
+        ArrangeOp::DistributeVertical,
+        ArrangeOp::MatchSpacingHorizontal,
+        ArrangeOp::MatchSpacingVertical,
+    ];
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            ArrangeOp::AlignLeft => "Align Left",
+            ArrangeOp::AlignRight => "Align Right",
+            ArrangeOp::AlignTop => "Align Top",
+            ArrangeOp::AlignBottom => "Align Bottom",
+            ArrangeOp::AlignCenterHorizontal => "Align Centers Horizontally",
+            ArrangeOp::AlignCenterVertical => "Align Centers Vertically",
+            ArrangeOp::DistributeHorizontal => "Distribute Horizontally",
+            ArrangeOp::DistributeVertical => "Distribute Vertically",
+            ArrangeOp::MatchSpacingHorizontal => "Match Horizontal Spacing",
+            ArrangeOp::MatchSpacingVertical => "Match Vertical Spacing",
+        }
+    }
+
+    /// Compute how much each of the given bounding boxes should move
+    fn compute_deltas(self, bounds: &[Rect]) -> Vec<Vec2> {
+        let mut deltas = vec![Vec2::ZERO; bounds.len()];
+        if bounds.len() < 2 {
+            return deltas;
+        }
+        let group = bounds.iter().skip(1).fold(bounds[0], |acc, r| Rect {
+            min: acc.min.min(r.min),
+            max: acc.max.max(r.max),
+        });
+        match self {
+            ArrangeOp::AlignLeft => for (d, r) in deltas.iter_mut().zip(bounds) {
+                d.x = group.min.x - r.min.x;
+            },
+            ArrangeOp::AlignRight => for (d, r) in deltas.iter_mut().zip(bounds) {
+                d.x = group.max.x - r.max.x;
+            },
+            ArrangeOp::AlignTop => for (d, r) in deltas.iter_mut().zip(bounds) {
+                d.y = group.max.y - r.max.y;
+            },
+            ArrangeOp::AlignBottom => for (d, r) in deltas.iter_mut().zip(bounds) {
+                d.y = group.min.y - r.min.y;
+            },
+            ArrangeOp::AlignCenterHorizontal => for (d, r) in deltas.iter_mut().zip(bounds) {
+                d.x = group.center().x - r.center().x;
+            },
+            ArrangeOp::AlignCenterVertical => for (d, r) in deltas.iter_mut().zip(bounds) {
+                d.y = group.center().y - r.center().y;
+            },
+            ArrangeOp::DistributeHorizontal => distribute(bounds, &mut deltas, |v| &mut v.x),
+            ArrangeOp::DistributeVertical => distribute(bounds, &mut deltas, |v| &mut v.y),
+            ArrangeOp::MatchSpacingHorizontal => match_spacing(bounds, &mut deltas, |v| &mut v.x),
+            ArrangeOp::MatchSpacingVertical => match_spacing(bounds, &mut deltas, |v| &mut v.y),
+        }
+        deltas
+    }
+}
+
+/// Space out the centers evenly along one axis
+fn distribute(bounds: &[Rect], deltas: &mut [Vec2], axis: impl Fn(&mut Vec2) -> &mut f32) {
+    let get = |mut v: Vec2| *axis(&mut v);
+    let mut order: Vec<usize> = (0..bounds.len()).collect();
+    order.sort_by(|a, b| get(bounds[*a].center()).total_cmp(&get(bounds[*b].center())));
+    let first = get(bounds[order[0]].center());
+    let last = get(bounds[order[order.len() - 1]].center());
+    let step = (last - first) / (order.len() - 1) as f32;
+    for (i, idx) in order.into_iter().enumerate() {
+        *axis(&mut deltas[idx]) = first + step * i as f32 - get(bounds[idx].center());
+    }
+}
+
+/// Make the gaps between consecutive bounding boxes equal along one axis
+fn match_spacing(bounds: &[Rect], deltas: &mut [Vec2], axis: impl Fn(&mut Vec2) -> &mut f32) {
+    let get = |mut v: Vec2| *axis(&mut v);
+    let mut order: Vec<usize> = (0..bounds.len()).collect();
+    order.sort_by(|a, b| get(bounds[*a].min).total_cmp(&get(bounds[*b].min)));
+    let start = get(bounds[order[0]].min);
+    let end = order.iter().map(|i| get(bounds[*i].max)).fold(f32::MIN, f32::max);
+    let total_size: f32 = bounds.iter().map(|r| get(r.size())).sum();
+    let gap = (end - start - total_size) / (order.len() - 1) as f32;
+    let mut cursor = start;
+    for idx in order {
+        *axis(&mut deltas[idx]) = cursor - get(bounds[idx].min);
+        cursor += get(bounds[idx].size()) + gap;
+    }
+}
+
+/// Apply an align/distribute operation to the current selection
+pub(crate) fn arrange_selection(
+    op: ArrangeOp,
+    q_selection: &Query<(&Selection, &SelectionVisualBounds)>,
+    q_target: &mut Query<(&mut Transform, &GlobalTransform, Option<&Parent>)>,
+    q_global: &Query<&GlobalTransform>,
+    q_parent: &Query<&Parent>,
+    history: &mut EditorHistory,
+) {
+    let mut entities = vec![];
+    let mut bounds = vec![];
+    for (sel, selbounds) in q_selection {
+        if let Ok((_, xf_global, _)) = q_target.get(sel.target) {
+            entities.push(sel.target);
+            bounds.push(world_bounds(selbounds.rect, xf_global));
+        }
+    }
+    let deltas: HashMap<Entity, Vec2> = entities.iter().copied()
+        .zip(op.compute_deltas(&bounds))
+        .collect();
+
+    // If a parent also moves, its children move along with it, so we
+    // must account for that when computing the children's new local transform.
+    // Everything moves only by translation, so any entity's new global
+    // transform is just its old one, plus the delta of the closest moved ancestor.
+    // Entities that stay in place (zero delta) are kept here, so they
+    // stop the search and don't inherit the delta of their ancestors.
+    let moved_delta = |mut e: Entity| -> Vec2 {
+        loop {
+            if let Some(d) = deltas.get(&e) {
+                return *d;
+            }
+            let Ok(parent) = q_parent.get(e) else {
+                return Vec2::ZERO;
+            };
+            e = parent.get();
+        }
+    };
+
+    let mut step = HistoryStep::default();
+    for (&entity, delta) in deltas.iter() {
+        let Ok((xf, xf_global, parent)) = q_target.get(entity) else {
+            continue;
+        };
+        // nothing to do, unless an ancestor moves and we must stay in place
+        if *delta == Vec2::ZERO && parent.map_or(true, |p| moved_delta(p.get()) == Vec2::ZERO) {
+            continue;
+        }
+        let mut target = xf_global.compute_transform();
+        target.translation += delta.extend(0.0);
+        let parent_global = parent.and_then(|p| {
+            let xf_parent = q_global.get(p.get()).ok()?;
+            let parent_delta = moved_delta(p.get());
+            Some(GlobalTransform::from(Transform::from_matrix(
+                Mat4::from_translation(parent_delta.extend(0.0)) * xf_parent.compute_matrix()
+            )))
+        });
+        step.transforms.push(TransformChange {
+            entity,
+            before: *xf,
+            after: world_to_local(target, parent_global.as_ref()),
+        });
+    }
+    for change in &step.transforms {
+        if let Ok((mut xf, _, _)) = q_target.get_mut(change.entity) {
+            *xf = change.after;
+        }
+    }
+    history.push(step);
+}
+
+/// Populate a menu with items for all the align/distribute operations
+pub(crate) fn spawn_arrange_menu(
+    commands: &mut Commands,
+    assets: &EditorAssets,
+    parent_menu: Entity,
+) {
+    for op in ArrangeOp::ALL {
+        crate::ui::menu::spawn_menuitem_action(
+            commands, assets, parent_menu, op.name(),
+            move |
+                q_selection: Query<(&Selection, &SelectionVisualBounds)>,
+                mut q_target: Query<(&mut Transform, &GlobalTransform, Option<&Parent>)>,
+                q_global: Query<&GlobalTransform>,
+                q_parent: Query<&Parent>,
+                mut history: ResMut<EditorHistory>,
+            | {
+                arrange_selection(op, &q_selection, &mut q_target, &q_global, &q_parent, &mut history);
+            }
+        );
+    }
+}
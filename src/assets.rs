@@ -1,6 +1,40 @@
+use std::path::Path;
+
+use bevy::asset::LoadState;
 use crate::crate_prelude::*;
 use bevy_asset_loader::prelude::*;
 
+#[cfg(feature = "embedded_assets")]
+mod embedded;
+
+/// Lists the files for all the editor's asset keys
+const ASSET_PACK_FILE: &str = "iyes2d_editor.assets.ron";
+
+/// Loaded for keys missing from the asset pack, so that they fail to load
+const MISSING_ASSET_PATH: &str = "iyes2d_editor.missing";
+
+/// All the keys used by `EditorAssets`
+const ASSET_KEYS: &[&str] = &[
+    "iyes2d_editor.font.regular",
+    "iyes2d_editor.font.bold",
+    "logo.small",
+    "iyes2d_editor.image.ui.smallbutt.depressed",
+    "iyes2d_editor.image.ui.smallbutt.pressed",
+    "iyes2d_editor.image.ui.smallbutt.hover",
+    "iyes2d_editor.image.ui.toolbar.depressed",
+    "iyes2d_editor.image.ui.toolbar.pressed",
+    "iyes2d_editor.image.ui.toolbar.hover",
+    "iyes2d_editor.image.ui.toolbar.disabled",
+    "iyes2d_editor.image.icon.wm.close",
+    "iyes2d_editor.image.icon.wm.minify",
+    "iyes2d_editor.image.icon.tool.selectentities",
+    "iyes2d_editor.image.icon.tool.translation",
+    "iyes2d_editor.image.icon.tool.rotation",
+    "iyes2d_editor.image.icon.tool.scale",
+    "iyes2d_editor.image.icon.tool.selecttilemap",
+    "iyes2d_editor.theme",
+];
+
 #[derive(AssetCollection, Resource)]
 pub struct EditorAssets {
     #[asset(key = "iyes2d_editor.font.regular")]
@@ -31,8 +65,14 @@ pub struct EditorAssets {
     pub(crate) image_icon_tool_selectentities: Handle<Image>,
     #[asset(key = "iyes2d_editor.image.icon.tool.translation")]
     pub(crate) image_icon_tool_translation: Handle<Image>,
+    #[asset(key = "iyes2d_editor.image.icon.tool.rotation")]
+    pub(crate) image_icon_tool_rotation: Handle<Image>,
+    #[asset(key = "iyes2d_editor.image.icon.tool.scale")]
+    pub(crate) image_icon_tool_scale: Handle<Image>,
     #[asset(key = "iyes2d_editor.image.icon.tool.selecttilemap")]
     pub(crate) image_icon_tool_selecttilemap: Handle<Image>,
+    #[asset(key = "iyes2d_editor.theme")]
+    pub(crate) theme: Handle<crate::ui::theme::EditorTheme>,
 }
 
 impl EditorAssets {
@@ -64,22 +104,114 @@ impl EditorAssets {
     }
 }
 
+/// Whether to show a message on screen if the editor's assets fail to load
+///
+/// The keys that failed are always logged as errors.
+#[derive(Resource, Debug, Clone)]
+pub struct ShowEditorAssetErrors(pub bool);
+
+impl Default for ShowEditorAssetErrors {
+    fn default() -> Self {
+        ShowEditorAssetErrors(true)
+    }
+}
+
+/// Present while the editor's assets are loading
+#[derive(Resource, Default)]
+struct LoadingEditorAssets;
+
+#[derive(Component)]
+struct AssetErrorMessage;
+
+/// Stands in for a key that is missing from the asset pack
+///
+/// It fails to load, so we end up in the failure state (and report it),
+/// rather than panicking when the collection is created.
+#[derive(Debug)]
+struct MissingAsset;
+
+impl DynamicAsset for MissingAsset {
+    fn load(&self, asset_server: &AssetServer) -> Vec<HandleUntyped> {
+        vec![asset_server.load_untyped(MISSING_ASSET_PATH)]
+    }
+
+    fn build(&self, _world: &mut World) -> Result<DynamicAssetType, bevy::asset::Error> {
+        Err(bevy::asset::Error::msg("missing from the asset pack"))
+    }
+}
+
 pub(crate) struct EditorAssetsPlugin<S: States> {
     pub asset_load_state: S,
+    pub asset_failure_state: Option<S>,
     pub editor_state: S,
 }
 
 impl<S: States> Plugin for EditorAssetsPlugin<S> {
     fn build(&self, app: &mut App) {
-        app.add_loading_state(
-            LoadingState::new(self.asset_load_state.clone())
-                .continue_to_state(self.editor_state.clone())
+        app.init_resource::<ShowEditorAssetErrors>();
+        let mut loading_state = LoadingState::new(self.asset_load_state.clone())
+            .continue_to_state(self.editor_state.clone());
+        if let Some(failure_state) = &self.asset_failure_state {
+            loading_state = loading_state.on_failure_continue_to_state(failure_state.clone());
+        }
+        app.add_loading_state(loading_state);
+        // with embedded assets, the asset pack is optional
+        #[cfg(feature = "embedded_assets")]
+        let use_asset_pack = {
+            embedded::register_embedded_assets(app);
+            embedded::asset_pack_exists(app, ASSET_PACK_FILE)
+        };
+        // the asset pack overrides these, if it has the keys
+        #[cfg(not(feature = "embedded_assets"))]
+        let use_asset_pack = {
+            app.init_resource::<DynamicAssets>();
+            let mut dynamic_assets = app.world.resource_mut::<DynamicAssets>();
+            for key in ASSET_KEYS {
+                dynamic_assets.register_asset(*key, Box::new(MissingAsset));
+            }
+            true
+        };
+        if use_asset_pack {
+            app.add_dynamic_collection_to_loading_state::<_, StandardDynamicAssetCollection>(
+                self.asset_load_state.clone(),
+                ASSET_PACK_FILE,
+            );
+        }
+        app.add_collection_to_loading_state::<_, EditorAssets>(self.asset_load_state.clone());
+        app.add_systems(
+            (
+                init_resource::<LoadingEditorAssets>,
+            ).in_schedule(OnEnter(self.asset_load_state.clone()))
         );
-        app.add_dynamic_collection_to_loading_state::<_, StandardDynamicAssetCollection>(
-            self.asset_load_state.clone(),
-            "iyes2d_editor.assets.ron"
+        app.add_systems(
+            (
+                remove_resource::<LoadingEditorAssets>,
+            ).in_schedule(OnEnter(self.editor_state.clone()))
         );
-        app.add_collection_to_loading_state::<_, EditorAssets>(self.asset_load_state.clone());
+        if let Some(failure_state) = &self.asset_failure_state {
+            app.add_systems(
+                (
+                    report_asset_failure,
+                ).in_schedule(OnEnter(failure_state.clone()))
+            );
+            app.add_systems(
+                (
+                    despawn_all_recursive::<With<AssetErrorMessage>>,
+                ).in_schedule(OnExit(failure_state.clone()))
+            );
+        } else {
+            // we stay in the loading state, so report from there
+            app.add_system(
+                report_asset_failure
+                    .run_if(in_state(self.asset_load_state.clone()))
+                    .run_if(editor_assets_failed)
+            );
+            app.add_systems(
+                (
+                    despawn_all_recursive::<With<AssetErrorMessage>>,
+                ).in_schedule(OnExit(self.asset_load_state.clone()))
+            );
+        }
         app.add_systems(
             (
                 remove_resource::<EditorAssets>,
@@ -87,3 +219,117 @@ impl<S: States> Plugin for EditorAssetsPlugin<S> {
         );
     }
 }
+
+/// Run condition: some of the editor's assets failed to load (without a failure state)
+///
+/// Then, `bevy_asset_loader` just keeps waiting in the loading state.
+fn editor_assets_failed(
+    asset_server: Res<AssetServer>,
+    dynamic_assets: Res<DynamicAssets>,
+    loading: Option<Res<LoadingEditorAssets>>,
+    mut pack_loaded: Local<bool>,
+) -> bool {
+    if loading.is_none() {
+        return false;
+    }
+    let pack_state = asset_server.get_load_state(ASSET_PACK_FILE);
+    if pack_state == LoadState::Failed {
+        return true;
+    }
+    // Until the keys from the asset pack are registered, missing keys
+    // would be reported by mistake. That happens once the pack is loaded,
+    // so wait one more frame after that.
+    let was_loaded = *pack_loaded;
+    // with embedded assets, there might not be an asset pack at all
+    *pack_loaded = pack_state == LoadState::Loaded ||
+        (cfg!(feature = "embedded_assets") && pack_state == LoadState::NotLoaded);
+    was_loaded && !failed_assets(&asset_server, &dynamic_assets).is_empty()
+}
+
+/// Describe all the keys that failed to load
+fn failed_assets(asset_server: &AssetServer, dynamic_assets: &DynamicAssets) -> Vec<String> {
+    let mut failed = vec![];
+    if asset_server.get_load_state(ASSET_PACK_FILE) == LoadState::Failed {
+        failed.push(format!("{}: could not be loaded", ASSET_PACK_FILE));
+    }
+    for key in ASSET_KEYS {
+        let Some(asset) = dynamic_assets.get_asset(key) else {
+            failed.push(format!("{}: missing from the asset pack", key));
+            continue;
+        };
+        for handle in asset.load(asset_server) {
+            if asset_server.get_load_state(&handle) != LoadState::Failed {
+                continue;
+            }
+            match asset_server.get_handle_path(&handle) {
+                Some(path) if path.path() == Path::new(MISSING_ASSET_PATH) => {
+                    failed.push(format!("{}: missing from the asset pack", key));
+                }
+                Some(path) => {
+                    failed.push(format!("{}: could not load {:?}", key, path.path()));
+                }
+                None => {
+                    failed.push(format!("{}: could not be loaded", key));
+                }
+            }
+        }
+    }
+    failed
+}
+
+/// Log (and optionally show) which keys failed, if we got here because loading failed
+fn report_asset_failure(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    dynamic_assets: Res<DynamicAssets>,
+    show: Res<ShowEditorAssetErrors>,
+    loading: Option<Res<LoadingEditorAssets>>,
+) {
+    if loading.is_none() {
+        return;
+    }
+    commands.remove_resource::<LoadingEditorAssets>();
+
+    let failed = failed_assets(&asset_server, &dynamic_assets);
+    let report = format!(
+        "Could not load the editor's assets (from {:?}):\n{}",
+        ASSET_PACK_FILE,
+        failed.join("\n"),
+    );
+    error!("{}", report);
+
+    if !show.0 {
+        return;
+    }
+    // our own assets are unavailable, so use Bevy's default font
+    let text = commands.spawn((
+        TextBundle {
+            text: Text::from_section(report, TextStyle {
+                font_size: 16.0,
+                color: Color::WHITE,
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+    )).id();
+    let message = commands.spawn((
+        NodeBundle {
+            background_color: BackgroundColor(Color::rgba(0.0, 0.0, 0.0, 0.8)),
+            z_index: ZIndex::Global(10000),
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(16.0),
+                    top: Val::Px(16.0),
+                    right: Val::Auto,
+                    bottom: Val::Auto,
+                },
+                padding: UiRect::all(Val::Px(8.0)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        AssetErrorMessage,
+    )).id();
+    commands.entity(message).push_children(&[text]);
+}
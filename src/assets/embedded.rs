@@ -0,0 +1,109 @@
+//! Default editor assets compiled into the binary (`embedded_assets` feature)
+//!
+//! Every key of the default `iyes2d_editor.assets.ron` is registered as a
+//! dynamic asset that is created from the embedded bytes. If the app's asset
+//! pack also has the key, that one is used instead, so the asset pack only
+//! needs to contain the things you want to replace (or can be omitted).
+
+use std::path::Path;
+
+use bevy::render::texture::{CompressedImageFormats, ImageType};
+use bevy_asset_loader::prelude::*;
+
+use crate::crate_prelude::*;
+use crate::ui::theme::EditorTheme;
+
+#[derive(Debug, Clone, Copy)]
+enum EmbeddedKind {
+    Font,
+    Image,
+    Theme,
+}
+
+/// An asset that is created from bytes in the binary, instead of loaded from a file
+#[derive(Debug)]
+struct EmbeddedAsset {
+    kind: EmbeddedKind,
+    bytes: &'static [u8],
+}
+
+macro_rules! embedded {
+    ($kind:ident, $path:literal) => {
+        EmbeddedAsset {
+            kind: EmbeddedKind::$kind,
+            bytes: include_bytes!(concat!("../../assets/", $path)),
+        }
+    };
+}
+
+/// Same as the default `iyes2d_editor.assets.ron`
+fn embedded_assets() -> Vec<(&'static str, EmbeddedAsset)> {
+    vec![
+        ("logo.small", embedded!(Image, "logo.png")),
+        ("iyes2d_editor.font.regular", embedded!(Font, "iyes2d_editor/font/Ubuntu-R.ttf")),
+        ("iyes2d_editor.font.bold", embedded!(Font, "iyes2d_editor/font/Ubuntu-B.ttf")),
+        ("iyes2d_editor.image.ui.smallbutt.depressed", embedded!(Image, "iyes2d_editor/image/ui/smallbutt_depressed.png")),
+        ("iyes2d_editor.image.ui.smallbutt.pressed", embedded!(Image, "iyes2d_editor/image/ui/smallbutt_pressed.png")),
+        ("iyes2d_editor.image.ui.smallbutt.hover", embedded!(Image, "iyes2d_editor/image/ui/smallbutt_hover.png")),
+        ("iyes2d_editor.image.ui.toolbar.depressed", embedded!(Image, "iyes2d_editor/image/ui/toolbar_depressed.png")),
+        ("iyes2d_editor.image.ui.toolbar.pressed", embedded!(Image, "iyes2d_editor/image/ui/toolbar_pressed.png")),
+        ("iyes2d_editor.image.ui.toolbar.hover", embedded!(Image, "iyes2d_editor/image/ui/toolbar_hover.png")),
+        ("iyes2d_editor.image.ui.toolbar.disabled", embedded!(Image, "iyes2d_editor/image/ui/toolbar_disabled.png")),
+        ("iyes2d_editor.image.icon.wm.minify", embedded!(Image, "iyes2d_editor/image/icon/wm/minify.png")),
+        ("iyes2d_editor.image.icon.wm.close", embedded!(Image, "iyes2d_editor/image/icon/wm/close.png")),
+        ("iyes2d_editor.image.icon.tool.selectentities", embedded!(Image, "iyes2d_editor/image/icon/tool/selectentities.png")),
+        ("iyes2d_editor.image.icon.tool.translation", embedded!(Image, "iyes2d_editor/image/icon/tool/translation.png")),
+        ("iyes2d_editor.image.icon.tool.rotation", embedded!(Image, "iyes2d_editor/image/icon/tool/rotation.png")),
+        ("iyes2d_editor.image.icon.tool.scale", embedded!(Image, "iyes2d_editor/image/icon/tool/scale.png")),
+        ("iyes2d_editor.image.icon.tool.selecttilemap", embedded!(Image, "iyes2d_editor/image/icon/tool/selecttilemap.png")),
+        ("iyes2d_editor.theme", embedded!(Theme, "iyes2d_editor/theme/light.theme.ron")),
+    ]
+}
+
+impl DynamicAsset for EmbeddedAsset {
+    fn load(&self, _asset_server: &AssetServer) -> Vec<HandleUntyped> {
+        // nothing to wait for
+        vec![]
+    }
+
+    fn build(&self, world: &mut World) -> Result<DynamicAssetType, bevy::asset::Error> {
+        let handle = match self.kind {
+            EmbeddedKind::Font => {
+                let font = Font::try_from_bytes(self.bytes.to_vec())?;
+                world.resource_mut::<Assets<Font>>().add(font).clone_untyped()
+            }
+            EmbeddedKind::Image => {
+                let image = Image::from_buffer(
+                    self.bytes,
+                    ImageType::Extension("png"),
+                    CompressedImageFormats::NONE,
+                    true,
+                )?;
+                world.resource_mut::<Assets<Image>>().add(image).clone_untyped()
+            }
+            EmbeddedKind::Theme => {
+                let theme: EditorTheme = ron::de::from_bytes(self.bytes)?;
+                world.resource_mut::<Assets<EditorTheme>>().add(theme).clone_untyped()
+            }
+        };
+        Ok(DynamicAssetType::Single(handle))
+    }
+}
+
+/// Register the embedded assets as defaults for all the editor's asset keys
+pub(super) fn register_embedded_assets(app: &mut App) {
+    app.init_resource::<DynamicAssets>();
+    let mut dynamic_assets = app.world.resource_mut::<DynamicAssets>();
+    for (key, asset) in embedded_assets() {
+        dynamic_assets.register_asset(key, Box::new(asset));
+    }
+}
+
+/// Check if the app has its own asset pack file (keys from it override the embedded ones)
+pub(super) fn asset_pack_exists(app: &App, file: &str) -> bool {
+    let Some(asset_server) = app.world.get_resource::<AssetServer>() else {
+        // can't tell; try loading it anyway
+        return true;
+    };
+    asset_server.asset_io().get_metadata(Path::new(file)).is_ok()
+}
@@ -0,0 +1,280 @@
+//! Editing of texture atlas sprites
+//!
+//! The "Atlas Frame" panel shows all the frames of the atlas used by the
+//! selected `TextureAtlasSprite`, as a grid of previews. Clicking on a
+//! frame changes the sprite's `index` to show that frame.
+
+use bevy::asset::HandleId;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+use crate::crate_prelude::*;
+use crate::selection::Selected;
+use crate::ui::panel::EditorPanelAppExt;
+
+/// Size (in pixels) of the frame previews in the panel
+const PREVIEW_SIZE: f32 = 32.0;
+
+/// How many frame previews to show per row
+const PREVIEW_COLUMNS: usize = 8;
+
+/// Don't show more frames than this, to avoid spawning huge UIs
+const MAX_FRAMES: usize = 256;
+
+pub(crate) struct AtlasEditorPlugin<S: States> {
+    pub state: S,
+}
+
+impl<S: States> Plugin for AtlasEditorPlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.add_editor_panel("Atlas Frame", populate_atlas_picker);
+        app.add_systems(
+            (
+                init_resource::<AtlasFramePreviews>,
+            ).in_schedule(OnEnter(self.state.clone()))
+        );
+        app.add_systems(
+            (
+                remove_resource::<AtlasFramePreviews>,
+            ).in_schedule(OnExit(self.state.clone()))
+        );
+        app.add_systems(
+            (
+                update_atlas_picker,
+            ).in_set(EditorSet)
+        );
+    }
+}
+
+/// Cropped images of individual atlas frames, for displaying in the UI
+#[derive(Resource, Default)]
+struct AtlasFramePreviews {
+    frames: HashMap<(HandleId, usize), Handle<Image>>,
+}
+
+/// The UI node containing the frame buttons
+#[derive(Component, Default)]
+struct AtlasPickerGrid {
+    /// What is currently displayed, so we know when to rebuild
+    shown: Option<AtlasPickerState>,
+    label: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct AtlasPickerState {
+    target: Entity,
+    atlas: HandleId,
+    index: usize,
+    n_frames: usize,
+    image_loaded: bool,
+}
+
+/// Marker for the text describing what the picker shows
+#[derive(Component)]
+struct AtlasPickerLabel;
+
+/// Button for choosing a frame
+#[derive(Component)]
+struct AtlasFrameButt {
+    target: Entity,
+    index: usize,
+}
+
+fn populate_atlas_picker(
+    In(e_contents): In<Entity>,
+    mut commands: Commands,
+    assets: Res<EditorAssets>,
+) {
+    let label = commands.spawn((
+        TextBundle {
+            text: Text::from_section("", TextStyle {
+                font: assets.font.clone(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        ThemeText::default(),
+        AtlasPickerLabel,
+    )).id();
+    let grid = commands.spawn((
+        NodeBundle {
+            style: Style {
+                flex_wrap: FlexWrap::Wrap,
+                max_size: Size::width(Val::Px((PREVIEW_SIZE + 4.0) * PREVIEW_COLUMNS as f32)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        AtlasPickerGrid::default(),
+    )).id();
+    commands.entity(e_contents).push_children(&[label, grid]);
+}
+
+fn update_atlas_picker(
+    mut commands: Commands,
+    assets: Res<EditorAssets>,
+    atlases: Res<Assets<TextureAtlas>>,
+    mut images: ResMut<Assets<Image>>,
+    mut previews: ResMut<AtlasFramePreviews>,
+    mut evr_atlas: EventReader<AssetEvent<TextureAtlas>>,
+    q_selected: Query<(Entity, &TextureAtlasSprite, &Handle<TextureAtlas>), With<Selected>>,
+    mut q_grid: Query<(Entity, &mut AtlasPickerGrid)>,
+    mut q_label: Query<&mut Text, With<AtlasPickerLabel>>,
+) {
+    // the frames may be different now
+    if evr_atlas.iter().any(|ev| matches!(ev, AssetEvent::Modified { .. })) {
+        previews.frames.clear();
+        for (_, mut grid) in &mut q_grid {
+            grid.label.clear();
+        }
+    }
+
+    let mut iter = q_selected.iter();
+    let target = match (iter.next(), iter.next()) {
+        (Some(single), None) => Some(single),
+        _ => None,
+    };
+    let state = target.map(|(e, sprite, handle)| {
+        let atlas = atlases.get(handle);
+        AtlasPickerState {
+            target: e,
+            atlas: handle.id(),
+            index: sprite.index,
+            n_frames: atlas.map(|a| a.textures.len()).unwrap_or(0),
+            image_loaded: atlas.map(|a| images.contains(&a.texture)).unwrap_or(false),
+        }
+    });
+
+    let label = match &state {
+        None if q_selected.is_empty() => "Select a texture atlas sprite.".to_owned(),
+        None => "Select only one texture atlas sprite.".to_owned(),
+        Some(state) if state.n_frames == 0 => "Atlas not loaded.".to_owned(),
+        Some(state) => format!("Frame {} of {}", state.index, state.n_frames),
+    };
+
+    for (e_grid, mut grid) in &mut q_grid {
+        if grid.shown == state && grid.label == label {
+            continue;
+        }
+        grid.shown = state.clone();
+        grid.label = label.clone();
+        commands.entity(e_grid).despawn_descendants();
+        for mut text in &mut q_label {
+            text.sections[0].value = label.clone();
+        }
+
+        let Some(state) = &state else {
+            continue;
+        };
+        let Some(atlas) = atlases.get(&Handle::weak(state.atlas)) else {
+            continue;
+        };
+        for (i, rect) in atlas.textures.iter().enumerate().take(MAX_FRAMES) {
+            let butt = commands.spawn((
+                ButtonBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(PREVIEW_SIZE + 4.0), Val::Px(PREVIEW_SIZE + 4.0)),
+                        padding: UiRect::all(Val::Px(2.0)),
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                AtlasFrameButt {
+                    target: state.target,
+                    index: i,
+                },
+                ThemeBackground(if i == state.index {
+                    |t| t.colors.item_selected
+                } else {
+                    |t| t.colors.item_background
+                }),
+                ClickBehavior::new().entity_system(atlas_frame_handler),
+                crate::ui::tooltip::TooltipText {
+                    title: format!("Frame {}", i),
+                    text: format!("{}x{} pixels at ({}, {})", rect.width(), rect.height(), rect.min.x, rect.min.y),
+                },
+            )).id();
+            let preview = previews.frames.get(&(state.atlas, i)).cloned().or_else(|| {
+                let cropped = crop_frame(images.get(&atlas.texture)?, *rect)?;
+                let handle = images.add(cropped);
+                previews.frames.insert((state.atlas, i), handle.clone());
+                Some(handle)
+            });
+            let child = if let Some(preview) = preview {
+                // fit the preview into the button, keeping the aspect ratio
+                let size = rect.size() * (PREVIEW_SIZE / rect.size().max_element());
+                commands.spawn((
+                    ImageBundle {
+                        focus_policy: FocusPolicy::Pass,
+                        style: Style {
+                            size: Size::new(Val::Px(size.x), Val::Px(size.y)),
+                            ..Default::default()
+                        },
+                        image: UiImage::new(preview),
+                        ..Default::default()
+                    },
+                )).id()
+            } else {
+                commands.spawn((
+                    TextBundle {
+                        focus_policy: FocusPolicy::Pass,
+                        text: Text::from_section(format!("{}", i), TextStyle {
+                            font: assets.font.clone(),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    },
+                    ThemeText::default(),
+                )).id()
+            };
+            commands.entity(butt).push_children(&[child]);
+            commands.entity(e_grid).push_children(&[butt]);
+        }
+    }
+}
+
+fn atlas_frame_handler(
+    In(entity): In<Entity>,
+    q_butt: Query<&AtlasFrameButt>,
+    mut q_sprite: Query<&mut TextureAtlasSprite>,
+) {
+    let Ok(butt) = q_butt.get(entity) else { return; };
+    if let Ok(mut sprite) = q_sprite.get_mut(butt.target) {
+        sprite.index = butt.index;
+    }
+}
+
+/// Copy a region of an image into a new image
+///
+/// Returns `None` if the texture format is not supported.
+fn crop_frame(image: &Image, rect: Rect) -> Option<Image> {
+    let format = image.texture_descriptor.format;
+    let bytes_per_pixel = match format {
+        TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb |
+        TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => 4,
+        _ => return None,
+    };
+    let width = image.texture_descriptor.size.width as usize;
+    let min = rect.min.as_uvec2();
+    let size = rect.max.as_uvec2().max(min) - min;
+    if size.x == 0 || size.y == 0 {
+        return None;
+    }
+    let mut data = Vec::with_capacity((size.x * size.y) as usize * bytes_per_pixel);
+    for y in min.y..(min.y + size.y) {
+        let start = (y as usize * width + min.x as usize) * bytes_per_pixel;
+        let end = start + size.x as usize * bytes_per_pixel;
+        data.extend_from_slice(image.data.get(start..end)?);
+    }
+    Some(Image::new(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        format,
+    ))
+}
@@ -1,4 +1,7 @@
 use bevy::{input::mouse::{MouseMotion, MouseWheel}, window::PrimaryWindow};
+use bevy::core_pipeline::clear_color::ClearColorConfig;
+use bevy::render::view::RenderLayers;
+use serde::{Deserialize, Serialize};
 use crate::crate_prelude::*;
 
 #[derive(SystemSet, Debug, PartialEq, Eq, Clone, Copy, Hash, Default)]
@@ -17,9 +20,14 @@ pub(crate) struct CameraPlugin<S: States> {
 impl<S: States> Plugin for CameraPlugin<S> {
     fn build(&self, app: &mut App) {
         app.init_resource::<WorldCursor>();
+        app.init_resource::<CameraControls>();
+        app.register_type::<CameraBookmark>();
+        app.register_type::<Vec<CameraBookmark>>();
+        app.register_type::<CameraBookmarks>();
         app.add_systems(
             (
                 ensure_setup_editor_camera,
+                setup_editor_ui_camera,
                 showhide_other_cameras::<false>,
             ).in_schedule(OnEnter(self.state.clone()))
         );
@@ -44,19 +52,74 @@ impl<S: States> Plugin for CameraPlugin<S> {
             .in_set(EditorSet)
             .in_set(CameraControlSet)
         );
+        app.add_systems(
+            (
+                camera_bookmark_keys.run_if(crate::text::not_editing_text),
+                update_bookmark_menu_labels,
+            ).in_set(CameraSet)
+            .in_set(EditorSet)
+            .after(CameraControlSet)
+        );
     }
 }
 
 #[derive(Component)]
 pub struct EditorCamera;
 
+/// Mouse/keyboard bindings and sensitivity for controlling the editor camera
+///
+/// Saved with the editor settings.
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CameraControls {
+    /// Hold to pan the camera
+    pub pan_button: MouseButton,
+    /// Hold and move the mouse horizontally to rotate the camera
+    pub rotate_button: MouseButton,
+    /// Radians of rotation per pixel of mouse motion
+    pub rotate_sensitivity: f32,
+    /// Hold and scroll to zoom
+    pub zoom_modifier: KeyCode,
+    /// How much each scroll step zooms by
+    pub zoom_factor: f32,
+    /// Minimum time between zoom steps
+    pub zoom_interval: Duration,
+}
+
+impl Default for CameraControls {
+    fn default() -> Self {
+        CameraControls {
+            pan_button: MouseButton::Right,
+            rotate_button: MouseButton::Middle,
+            rotate_sensitivity: 1.0 / 256.0,
+            zoom_modifier: KeyCode::LShift,
+            zoom_factor: 2.0,
+            zoom_interval: Duration::from_millis(125),
+        }
+    }
+}
+
+/// Marker for additional cameras owned by the editor (such as the minimap)
+///
+/// These are not hidden/shown together with the game's cameras
+/// when entering/exiting the editor.
+#[derive(Component)]
+pub struct EditorAuxCamera;
+
+/// Camera that only renders the editor UI, over the whole window
+///
+/// The editor camera does not render UI, because its viewport can be
+/// smaller than the window (when panels are docked).
+#[derive(Component)]
+pub struct EditorUiCamera;
+
 #[derive(Resource, Default)]
 pub(crate) struct WorldCursor {
     pub pos: Vec2,
 }
 
 fn showhide_other_cameras<const VIS: bool>(
-    mut q_camera: Query<&mut Visibility, (With<Camera>, Without<EditorCamera>)>,
+    mut q_camera: Query<&mut Visibility, (With<Camera>, Without<EditorCamera>, Without<EditorAuxCamera>)>,
 ) {
     for mut vis in &mut q_camera {
         *vis = if VIS {
@@ -93,19 +156,59 @@ fn ensure_setup_editor_camera(
         }
     };
     // TODO: enforce things we care about on the camera, here
+    commands.entity(e_camera).insert(UiCameraConfig {
+        show_ui: false,
+    });
+}
+
+fn setup_editor_ui_camera(
+    mut commands: Commands,
+) {
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera {
+                order: 1000,
+                ..Default::default()
+            },
+            camera_2d: Camera2d {
+                clear_color: ClearColorConfig::None,
+            },
+            ..Default::default()
+        },
+        // nothing in the world is on this layer
+        RenderLayers::layer(RenderLayers::TOTAL_LAYERS as u8 - 1),
+        EditorUiCamera,
+        EditorAuxCamera,
+        EditorCleanup,
+    ));
+}
+
+/// Convert a window cursor position into a position within the camera's viewport
+///
+/// Both have the origin at the bottom left.
+pub(crate) fn cursor_to_viewport(camera: &Camera, window: &Window, pos: Vec2) -> Vec2 {
+    let Some(viewport) = &camera.viewport else {
+        return pos;
+    };
+    let scale = window.scale_factor() as f32;
+    // the viewport's position is from the top left
+    let min = viewport.physical_position.as_vec2() / scale;
+    let size = viewport.physical_size.as_vec2() / scale;
+    Vec2::new(pos.x - min.x, pos.y - (window.height() - min.y - size.y))
 }
 
 fn camera_pan(
+    controls: Res<CameraControls>,
     mousebutt: Res<Input<MouseButton>>,
     crs: Res<WorldCursor>,
     mut q_camera: Query<&mut Transform, With<EditorCamera>>,
     mut startpos: Local<Vec2>,
 ) {
     // TODO: transition to a proper input mgmt framework like LWIM
-    if mousebutt.just_pressed(MouseButton::Right) {
+    if mousebutt.just_pressed(controls.pan_button) {
         *startpos = crs.pos;
     }
-    if mousebutt.pressed(MouseButton::Right) {
+    if mousebutt.pressed(controls.pan_button) {
         // pan based on WorldCursor, so camera follows on-screen nicely
         // this system must run *after* world cursor, or they will race
         // (mathematically, next frame the world cursor should be in the same place)
@@ -117,34 +220,36 @@ fn camera_pan(
 }
 
 fn camera_rotate(
+    controls: Res<CameraControls>,
     mousebutt: Res<Input<MouseButton>>,
     mut motion: EventReader<MouseMotion>,
     mut q_camera: Query<&mut Transform, With<EditorCamera>>,
 ) {
     // TODO: transition to a proper input mgmt framework like LWIM
-    if mousebutt.pressed(MouseButton::Middle) {
+    if mousebutt.pressed(controls.rotate_button) {
         let delta: f32 = motion.iter().map(|ev| ev.delta.x).sum();
         if delta != 0.0 {
             let mut xf_cam = q_camera.single_mut();
-            xf_cam.rotate_z(delta / 256.0);
+            xf_cam.rotate_z(delta * controls.rotate_sensitivity);
         }
     }
 }
 
 fn camera_zoom(
+    controls: Res<CameraControls>,
     kbd: Res<Input<KeyCode>>,
     mut motion: EventReader<MouseWheel>,
     mut last_zoom: Local<Option<Instant>>,
     mut q_camera: Query<&mut Transform, With<EditorCamera>>,
 ) {
-    if !kbd.pressed(KeyCode::LShift) {
+    if !kbd.pressed(controls.zoom_modifier) {
         return;
     }
 
     // TODO: this feels awful but will do for now
     // just throttle how often we can zoom
     if let Some(last) = &*last_zoom {
-        if Instant::now() - *last < Duration::from_millis(125) {
+        if Instant::now() - *last < controls.zoom_interval {
             motion.clear();
             return;
         }
@@ -156,9 +261,9 @@ fn camera_zoom(
         let mut xf_cam = q_camera.single_mut();
         // let mul = delta.exp();
         let mul = if delta < 0.0 {
-            0.5
+            1.0 / controls.zoom_factor
         } else {
-            2.0
+            controls.zoom_factor
         };
         xf_cam.scale.x *= mul;
         xf_cam.scale.y *= mul;
@@ -166,6 +271,164 @@ fn camera_zoom(
     }
 }
 
+/// A saved view of the editor camera
+#[derive(Debug, Clone, Copy, Default, Reflect, FromReflect)]
+pub struct CameraBookmark {
+    /// Which number key (1-9) this bookmark is bound to
+    pub slot: u8,
+    pub translation: Vec2,
+    pub zoom: f32,
+    /// Rotation around the Z axis, in radians
+    pub rotation: f32,
+}
+
+impl CameraBookmark {
+    fn from_transform(slot: u8, xf: &Transform) -> Self {
+        CameraBookmark {
+            slot,
+            translation: xf.translation.truncate(),
+            zoom: xf.scale.x,
+            rotation: xf.rotation.to_euler(EulerRot::ZYX).0,
+        }
+    }
+
+    fn apply_to_transform(&self, xf: &mut Transform) {
+        xf.translation.x = self.translation.x;
+        xf.translation.y = self.translation.y;
+        xf.scale.x = self.zoom;
+        xf.scale.y = self.zoom;
+        xf.rotation = Quat::from_rotation_z(self.rotation);
+    }
+}
+
+/// Camera bookmarks belonging to the level being edited
+///
+/// This is stored as a component on a regular entity in the world (not
+/// cleaned up when exiting the editor), so that it gets exported together
+/// with the rest of your level if you save it as a scene. That way, everyone
+/// working on the level gets the same bookmarks.
+///
+/// Ctrl+1..9 stores the current view of the editor camera, 1..9 recalls it.
+#[derive(Component, Debug, Clone, Default, Reflect, FromReflect)]
+#[reflect(Component)]
+pub struct CameraBookmarks {
+    pub bookmarks: Vec<CameraBookmark>,
+}
+
+impl CameraBookmarks {
+    pub fn get(&self, slot: u8) -> Option<&CameraBookmark> {
+        self.bookmarks.iter().find(|bm| bm.slot == slot)
+    }
+
+    pub fn set(&mut self, bookmark: CameraBookmark) {
+        if let Some(bm) = self.bookmarks.iter_mut().find(|bm| bm.slot == bookmark.slot) {
+            *bm = bookmark;
+        } else {
+            self.bookmarks.push(bookmark);
+            self.bookmarks.sort_by_key(|bm| bm.slot);
+        }
+    }
+}
+
+const BOOKMARK_KEYS: [KeyCode; 9] = [
+    KeyCode::Key1, KeyCode::Key2, KeyCode::Key3,
+    KeyCode::Key4, KeyCode::Key5, KeyCode::Key6,
+    KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+];
+
+fn camera_bookmark_keys(
+    mut commands: Commands,
+    kbd: Res<Input<KeyCode>>,
+    mut q_bookmarks: Query<&mut CameraBookmarks>,
+    mut q_camera: Query<&mut Transform, With<EditorCamera>>,
+) {
+    // TODO: transition to a proper input mgmt framework like LWIM
+    let Some(slot) = BOOKMARK_KEYS.iter()
+        .position(|key| kbd.just_pressed(*key))
+        .map(|i| i as u8 + 1)
+    else {
+        return;
+    };
+    let mut xf_cam = q_camera.single_mut();
+    if kbd.any_pressed([KeyCode::LControl, KeyCode::RControl]) {
+        let bookmark = CameraBookmark::from_transform(slot, &xf_cam);
+        if let Some(mut bookmarks) = q_bookmarks.iter_mut().next() {
+            bookmarks.set(bookmark);
+        } else {
+            commands.spawn((
+                Name::new("Editor Camera Bookmarks"),
+                EditorData,
+                CameraBookmarks {
+                    bookmarks: vec![bookmark],
+                },
+            ));
+        }
+        info!("Stored camera bookmark {}", slot);
+    } else if let Some(bookmark) = q_bookmarks.iter().next().and_then(|bms| bms.get(slot)) {
+        bookmark.apply_to_transform(&mut xf_cam);
+    }
+}
+
+/// Marker for the menu items that recall camera bookmarks
+#[derive(Component)]
+struct BookmarkMenuItem(u8);
+
+/// Populate a menu with items for recalling the camera bookmarks
+pub(crate) fn spawn_bookmarks_menu(
+    commands: &mut Commands,
+    assets: &EditorAssets,
+    parent_menu: Entity,
+) {
+    for slot in 1..=BOOKMARK_KEYS.len() as u8 {
+        let item = crate::ui::menu::spawn_menuitem_action(
+            commands, assets, parent_menu, &bookmark_menu_label(slot, None),
+            move |q_bookmarks: Query<&CameraBookmarks>, mut q_camera: Query<&mut Transform, With<EditorCamera>>| {
+                if let Some(bookmark) = q_bookmarks.iter().next().and_then(|bms| bms.get(slot)) {
+                    bookmark.apply_to_transform(&mut q_camera.single_mut());
+                }
+            }
+        );
+        commands.entity(item).insert(BookmarkMenuItem(slot));
+    }
+    crate::ui::menu::spawn_menuitem_action(
+        commands, assets, parent_menu, "Clear All Bookmarks",
+        |mut q_bookmarks: Query<&mut CameraBookmarks>| {
+            for mut bookmarks in &mut q_bookmarks {
+                bookmarks.bookmarks.clear();
+            }
+        }
+    );
+}
+
+fn bookmark_menu_label(slot: u8, bookmark: Option<&CameraBookmark>) -> String {
+    if let Some(bm) = bookmark {
+        format!("{}: ({:.0}, {:.0}) x{}", slot, bm.translation.x, bm.translation.y, bm.zoom)
+    } else {
+        format!("{}: (empty)", slot)
+    }
+}
+
+fn update_bookmark_menu_labels(
+    q_bookmarks: Query<Ref<CameraBookmarks>>,
+    q_item: Query<(&BookmarkMenuItem, &Children)>,
+    mut q_text: Query<&mut Text>,
+    mut removed: RemovedComponents<CameraBookmarks>,
+) {
+    let removed = removed.iter().next().is_some();
+    let bookmarks = q_bookmarks.iter().next();
+    let changed = bookmarks.as_ref().map(|bms| bms.is_changed()).unwrap_or(false);
+    if !changed && !removed {
+        return;
+    }
+    for (item, children) in &q_item {
+        let bookmark = bookmarks.as_ref().and_then(|bms| bms.get(item.0));
+        let mut iter = q_text.iter_many_mut(children.iter());
+        if let Some(mut text) = iter.fetch_next() {
+            text.sections[0].value = bookmark_menu_label(item.0, bookmark);
+        }
+    }
+}
+
 fn world_cursor(
     mut crs: ResMut<WorldCursor>,
     q_windows: Query<&Window, With<PrimaryWindow>>,
@@ -178,7 +441,7 @@ fn world_cursor(
     // };
     let Some(cursor) = q_windows
         .get_single().ok()
-        .and_then(|window| window.cursor_position())
+        .and_then(|window| window.cursor_position().map(|pos| cursor_to_viewport(camera, window, pos)))
         .and_then(|pos| camera.viewport_to_world(xf_camera, pos))
         .map(|ray| ray.origin.truncate())
     else {
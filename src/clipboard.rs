@@ -0,0 +1,48 @@
+//! Access to the OS clipboard (with the `system_clipboard` cargo feature)
+//!
+//! One `arboard::Clipboard` is kept alive for the whole app, because on
+//! Linux (X11), the process that copied something has to keep serving it,
+//! or other apps can't paste it.
+//!
+//! Without the feature, nothing is ever put on or read from the OS clipboard.
+
+use crate::crate_prelude::*;
+
+/// The OS clipboard (non-send resource)
+#[derive(Default)]
+pub(crate) struct SystemClipboard {
+    #[cfg(feature = "system_clipboard")]
+    clipboard: Option<arboard::Clipboard>,
+}
+
+impl SystemClipboard {
+    #[cfg(feature = "system_clipboard")]
+    fn get(&mut self) -> Option<&mut arboard::Clipboard> {
+        if self.clipboard.is_none() {
+            match arboard::Clipboard::new() {
+                Ok(clipboard) => self.clipboard = Some(clipboard),
+                Err(e) => warn!("Could not access the system clipboard: {}", e),
+            }
+        }
+        self.clipboard.as_mut()
+    }
+
+    pub(crate) fn set_text(&mut self, text: &str) {
+        #[cfg(feature = "system_clipboard")]
+        if let Some(clipboard) = self.get() {
+            if let Err(e) = clipboard.set_text(text.to_owned()) {
+                warn!("Could not copy to the system clipboard: {}", e);
+            }
+        }
+        #[cfg(not(feature = "system_clipboard"))]
+        let _ = text;
+    }
+
+    pub(crate) fn get_text(&mut self) -> Option<String> {
+        #[cfg(feature = "system_clipboard")]
+        let text = self.get().and_then(|clipboard| clipboard.get_text().ok());
+        #[cfg(not(feature = "system_clipboard"))]
+        let text = None;
+        text
+    }
+}
@@ -0,0 +1,248 @@
+//! Creating and removing entities: Delete, Duplicate, Copy/Paste
+//!
+//! All of these operate on the `Selected` entities, together with all
+//! their descendants (children).
+//!
+//! Duplicating and Copy/Paste work by cloning all the components that are
+//! registered for reflection (via Bevy's scene infrastructure). Components
+//! that are not registered in the type registry are not copied!
+//!
+//! Copying serializes the entities to a RON scene, which is stored in an
+//! internal buffer, and (with the `system_clipboard` cargo feature) also
+//! put on the OS clipboard as text. This allows pasting into another
+//! running instance of your app.
+//!
+//! Keyboard shortcuts: Delete, Ctrl+D, Ctrl+C, Ctrl+V.
+//!
+//! These are not recorded in the `EditorHistory` (it only tracks transform
+//! changes), so they cannot be undone.
+
+use bevy::ecs::entity::EntityMap;
+use bevy::scene::serde::SceneDeserializer;
+use serde::de::DeserializeSeed;
+
+use crate::crate_prelude::*;
+use crate::camera::WorldCursor;
+use crate::clipboard::SystemClipboard;
+use crate::selection::{Selected, SelectionVisualBounds, SelectionVisualColor};
+
+/// How far to offset duplicated entities from the originals
+const DUPLICATE_OFFSET: Vec2 = Vec2::new(8.0, -8.0);
+
+pub(crate) struct EntityOpsPlugin<S: States> {
+    pub state: S,
+}
+
+impl<S: States> Plugin for EntityOpsPlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditorClipboard>();
+        app.init_non_send_resource::<SystemClipboard>();
+        app.add_system(
+            entity_ops_keys
+                .in_set(EditorSet)
+                .run_if(crate::text::not_editing_text)
+        );
+    }
+}
+
+/// Internal clipboard buffer for copy/paste of entities
+///
+/// Contains a `DynamicScene` serialized as RON.
+#[derive(Resource, Default)]
+pub struct EditorClipboard {
+    pub ron: Option<String>,
+}
+
+fn entity_ops_keys(world: &mut World) {
+    // TODO: transition to a proper input mgmt framework like LWIM
+    let kbd = world.resource::<Input<KeyCode>>();
+    let ctrl = kbd.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    if kbd.just_pressed(KeyCode::Delete) {
+        delete_selected(world);
+    } else if ctrl && kbd.just_pressed(KeyCode::D) {
+        duplicate_selected(world);
+    } else if ctrl && kbd.just_pressed(KeyCode::C) {
+        copy_selected(world);
+    } else if ctrl && kbd.just_pressed(KeyCode::V) {
+        paste(world);
+    }
+}
+
+/// Selected entities that don't have any selected ancestors
+fn selected_roots(world: &mut World) -> Vec<Entity> {
+    let selected: HashSet<Entity> = world.query_filtered::<Entity, With<Selected>>()
+        .iter(world)
+        .collect();
+    let mut q_parent = world.query::<&Parent>();
+    selected.iter().copied()
+        .filter(|e| {
+            let mut e = *e;
+            while let Ok(parent) = q_parent.get(world, e) {
+                e = parent.get();
+                if selected.contains(&e) {
+                    return false;
+                }
+            }
+            true
+        })
+        .collect()
+}
+
+/// Create a scene containing the given entities and all their descendants
+///
+/// The given entities will become top-level (the scene does not contain their `Parent`).
+fn build_scene(world: &mut World, roots: &[Entity]) -> DynamicScene {
+    let mut q_children = world.query::<&Children>();
+    let mut entities = vec![];
+    let mut stack = roots.to_vec();
+    while let Some(e) = stack.pop() {
+        entities.push(e);
+        if let Ok(children) = q_children.get(world, e) {
+            stack.extend(children.iter().copied());
+        }
+    }
+
+    let mut builder = DynamicSceneBuilder::from_world(world);
+    builder.extract_entities(entities.into_iter());
+    let mut scene = builder.build();
+
+    let parent_type = std::any::type_name::<Parent>();
+    for dyn_entity in &mut scene.entities {
+        if roots.iter().any(|e| e.index() == dyn_entity.entity) {
+            dyn_entity.components.retain(|c| c.type_name() != parent_type);
+        }
+    }
+    scene
+}
+
+/// Despawn all selected entities (and their descendants)
+///
+/// This cannot be undone.
+pub fn delete_selected(world: &mut World) {
+    let roots = selected_roots(world);
+    // deselect everything first, so no selection visuals are left dangling
+    let selected: Vec<Entity> = world.query_filtered::<Entity, With<Selected>>()
+        .iter(world)
+        .collect();
+    for e in selected {
+        crate::selection::deselect_entity(world, e);
+    }
+    for e in roots {
+        if let Some(e) = world.get_entity_mut(e) {
+            e.despawn_recursive();
+        }
+    }
+}
+
+/// Clone all selected entities (and their descendants) and select the copies
+pub fn duplicate_selected(world: &mut World) {
+    let roots = selected_roots(world);
+    if roots.is_empty() {
+        return;
+    }
+    let scene = build_scene(world, &roots);
+    let mut entity_map = EntityMap::default();
+    if let Err(e) = scene.write_to_world(world, &mut entity_map) {
+        error!("Could not duplicate entities: {}", e);
+        return;
+    }
+
+    for old in roots {
+        let Ok(new) = entity_map.get(Entity::from_raw(old.index())) else {
+            continue;
+        };
+        // put the copy under the same parent as the original
+        if let Some(parent) = world.get::<Parent>(old).map(|p| p.get()) {
+            world.entity_mut(parent).push_children(&[new]);
+        }
+        if let Some(mut xf) = world.get_mut::<Transform>(new) {
+            xf.translation += DUPLICATE_OFFSET.extend(0.0);
+        }
+        // select the copy instead of the original, with the same visuals
+        let Some(selection) = world.get::<Selected>(old).map(|s| s.selection) else {
+            continue;
+        };
+        let (Some(bounds), Some(color)) = (
+            world.get::<SelectionVisualBounds>(selection).map(|b| b.rect),
+            world.get::<SelectionVisualColor>(selection).map(|c| c.0),
+        ) else {
+            continue;
+        };
+        crate::selection::deselect_entity(world, old);
+        crate::selection::select_entity(world, new, bounds, color);
+    }
+}
+
+/// Serialize all selected entities (and their descendants) into the clipboard
+pub fn copy_selected(world: &mut World) {
+    let roots = selected_roots(world);
+    if roots.is_empty() {
+        return;
+    }
+    let scene = build_scene(world, &roots);
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let ron = match scene.serialize_ron(&registry) {
+        Ok(ron) => ron,
+        Err(e) => {
+            error!("Could not serialize entities for copying: {}", e);
+            return;
+        }
+    };
+    world.non_send_resource_mut::<SystemClipboard>().set_text(&ron);
+    world.resource_mut::<EditorClipboard>().ron = Some(ron);
+}
+
+/// Spawn the entities from the clipboard, centered around the mouse cursor
+pub fn paste(world: &mut World) {
+    // prefer the system clipboard, because it might come from another instance
+    let system = world.non_send_resource_mut::<SystemClipboard>().get_text()
+        .filter(|text| text.trim_start().starts_with('('));
+    let ron = system.or_else(|| world.resource::<EditorClipboard>().ron.clone());
+
+    let Some(ron) = ron else {
+        return;
+    };
+
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let scene = {
+        let registry = registry.read();
+        let deserializer = SceneDeserializer {
+            type_registry: &registry,
+        };
+        let result = ron::de::Deserializer::from_str(&ron)
+            .map_err(|e| e.to_string())
+            .and_then(|mut de| deserializer.deserialize(&mut de).map_err(|e| e.to_string()));
+        match result {
+            Ok(scene) => scene,
+            Err(e) => {
+                error!("Could not deserialize entities for pasting: {}", e);
+                return;
+            }
+        }
+    };
+
+    let mut entity_map = EntityMap::default();
+    if let Err(e) = scene.write_to_world(world, &mut entity_map) {
+        error!("Could not paste entities: {}", e);
+        return;
+    }
+
+    // move the top-level entities, so that their center is at the cursor
+    let roots: Vec<Entity> = entity_map.values()
+        .filter(|e| world.get::<Parent>(*e).is_none())
+        .collect();
+    let positions: Vec<Vec2> = roots.iter()
+        .filter_map(|e| world.get::<Transform>(*e))
+        .map(|xf| xf.translation.truncate())
+        .collect();
+    if positions.is_empty() {
+        return;
+    }
+    let center = positions.iter().sum::<Vec2>() / positions.len() as f32;
+    let offset = world.resource::<WorldCursor>().pos - center;
+    for e in roots {
+        if let Some(mut xf) = world.get_mut::<Transform>(e) {
+            xf.translation += offset.extend(0.0);
+        }
+    }
+}
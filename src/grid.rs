@@ -0,0 +1,221 @@
+//! World-space background grid
+//!
+//! Drawn behind everything else in the editor view. The lines are just
+//! sprites, recycled every frame to cover the area visible in the editor
+//! camera. Line density adapts to the camera zoom: when the lines would get
+//! too close together on-screen, subdivisions are dropped and the grid gets
+//! coarser.
+
+use serde::{Deserialize, Serialize};
+
+use crate::crate_prelude::*;
+use crate::camera::EditorCamera;
+
+/// Hard limit to protect against pathological configurations
+const MAX_LINES_PER_AXIS: usize = 512;
+
+pub(crate) struct GridPlugin<S: States> {
+    pub state: S,
+}
+
+impl<S: States> Plugin for GridPlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditorGrid>();
+        app.add_system(
+            update_grid
+                .in_set(EditorSet)
+                .after(crate::camera::CameraControlSet)
+        );
+    }
+}
+
+/// Configuration for the editor's background grid
+///
+/// Insert/modify this resource to customize how the grid looks.
+/// Saved with the editor settings.
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EditorGrid {
+    pub enabled: bool,
+    /// Spacing of the major grid lines, in world units
+    pub cell_size: Vec2,
+    /// How many smaller cells each grid cell is divided into (1 = no subdivisions)
+    ///
+    /// When zooming out, this is also the factor by which the grid gets coarser.
+    pub subdivisions: u32,
+    /// Color of the major grid lines
+    pub color: Color,
+    /// Color of the subdivision lines
+    pub subdivision_color: Color,
+    /// Color of the line along the X axis (where Y = 0)
+    pub axis_x_color: Color,
+    /// Color of the line along the Y axis (where X = 0)
+    pub axis_y_color: Color,
+    /// Lines closer together than this many (logical) pixels on-screen will not be drawn
+    pub min_line_spacing: f32,
+}
+
+impl Default for EditorGrid {
+    fn default() -> Self {
+        EditorGrid {
+            enabled: true,
+            cell_size: Vec2::new(64.0, 64.0),
+            subdivisions: 4,
+            color: Color::rgba(1.0, 1.0, 1.0, 0.25),
+            subdivision_color: Color::rgba(1.0, 1.0, 1.0, 0.08),
+            axis_x_color: Color::rgba(1.0, 0.2, 0.2, 0.75),
+            axis_y_color: Color::rgba(0.2, 1.0, 0.2, 0.75),
+            min_line_spacing: 8.0,
+        }
+    }
+}
+
+#[derive(Component)]
+struct GridLine;
+
+#[derive(Clone, Copy)]
+enum LineKind {
+    Axis,
+    Major,
+    Minor,
+}
+
+/// Compute the positions of all lines along one axis, within the range `min..=max`
+fn grid_lines_1d(
+    out: &mut Vec<(f32, LineKind)>,
+    min: f32, max: f32,
+    cell_size: f32, subdivisions: u32,
+    world_per_px: f32, min_spacing: f32,
+) {
+    if cell_size <= 0.0 {
+        return;
+    }
+    let factor = subdivisions.max(2) as f32;
+    let min_world_spacing = min_spacing * world_per_px;
+    // coarsen the major lines until they are far enough apart
+    let mut major = cell_size;
+    while major < min_world_spacing {
+        major *= factor;
+    }
+    // subdivisions only if they are also far enough apart
+    let minor = major / subdivisions.max(1) as f32;
+    let (step, per_major) = if subdivisions > 1 && minor >= min_world_spacing {
+        (minor, subdivisions as i64)
+    } else {
+        (major, 1)
+    };
+
+    let first = (min / step).ceil() as i64;
+    let last = (max / step).floor() as i64;
+    if last - first > MAX_LINES_PER_AXIS as i64 {
+        return;
+    }
+    for i in first..=last {
+        let pos = i as f32 * step;
+        let kind = if i == 0 {
+            LineKind::Axis
+        } else if i % per_major == 0 {
+            LineKind::Major
+        } else {
+            LineKind::Minor
+        };
+        out.push((pos, kind));
+    }
+}
+
+fn update_grid(
+    mut commands: Commands,
+    grid: Res<EditorGrid>,
+    q_camera: Query<(&Camera, &GlobalTransform, &Transform, &OrthographicProjection), With<EditorCamera>>,
+    mut q_lines: Query<(&mut Sprite, &mut Transform, &mut Visibility), (With<GridLine>, Without<EditorCamera>)>,
+) {
+    let mut lines_x = vec![];
+    let mut lines_y = vec![];
+    let mut min = Vec2::ZERO;
+    let mut max = Vec2::ZERO;
+    let mut z = 0.0;
+    let mut world_per_px = 1.0;
+
+    if grid.enabled {
+        let Ok((camera, xf_camera, xf_camera_local, projection)) = q_camera.get_single() else {
+            return;
+        };
+        let Some(viewport_size) = camera.logical_viewport_size() else {
+            return;
+        };
+        // the area visible in the camera; if rotated, its bounding box
+        min = Vec2::splat(f32::MAX);
+        max = Vec2::splat(f32::MIN);
+        for corner in [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(viewport_size.x, 0.0),
+            Vec2::new(0.0, viewport_size.y),
+            viewport_size,
+        ] {
+            let Some(ray) = camera.viewport_to_world(xf_camera, corner) else {
+                return;
+            };
+            min = min.min(ray.origin.truncate());
+            max = max.max(ray.origin.truncate());
+        }
+        world_per_px = xf_camera_local.scale.x * projection.scale;
+        // as far back as the camera can see, so that everything else is in front
+        z = xf_camera.translation().z - projection.far + 0.001;
+
+        grid_lines_1d(&mut lines_x, min.x, max.x, grid.cell_size.x, grid.subdivisions, world_per_px, grid.min_line_spacing);
+        grid_lines_1d(&mut lines_y, min.y, max.y, grid.cell_size.y, grid.subdivisions, world_per_px, grid.min_line_spacing);
+    }
+
+    let color = |kind, axis_color| match kind {
+        LineKind::Axis => axis_color,
+        LineKind::Major => grid.color,
+        LineKind::Minor => grid.subdivision_color,
+    };
+    // vertical lines are at X positions, horizontal lines at Y positions
+    let center = (min + max) / 2.0;
+    let size = max - min;
+    let mut wanted = lines_x.iter().map(|&(x, kind)| (
+        Vec2::new(x, center.y),
+        Vec2::new(world_per_px, size.y),
+        color(kind, grid.axis_y_color),
+    )).chain(lines_y.iter().map(|&(y, kind)| (
+        Vec2::new(center.x, y),
+        Vec2::new(size.x, world_per_px),
+        color(kind, grid.axis_x_color),
+    )));
+
+    // recycle existing line entities
+    for (mut sprite, mut xf, mut vis) in &mut q_lines {
+        if let Some((pos, size, color)) = wanted.next() {
+            sprite.custom_size = Some(size);
+            sprite.color = color;
+            xf.translation = pos.extend(z);
+            *vis = Visibility::Inherited;
+        } else if *vis != Visibility::Hidden {
+            *vis = Visibility::Hidden;
+        }
+    }
+    // spawn more if needed
+    for (pos, size, color) in wanted {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(size),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(pos.extend(z)),
+                ..Default::default()
+            },
+            GridLine,
+            EditorCleanup,
+        ));
+    }
+}
+
+/// Menu action to show/hide the grid
+pub(crate) fn toggle_grid(
+    mut grid: ResMut<EditorGrid>,
+) {
+    grid.enabled = !grid.enabled;
+}
@@ -0,0 +1,120 @@
+//! Undo/Redo history
+//!
+//! Editing operations record what they changed into the `EditorHistory`
+//! resource, as one step per user action (a whole mouse drag, holding
+//! down a key, etc.). Ctrl+Z undoes the last step, Ctrl+Y (or Ctrl+Shift+Z)
+//! redoes it.
+//!
+//! Only transform changes are recorded. Deleting, duplicating and pasting
+//! entities cannot be undone.
+
+use crate::crate_prelude::*;
+
+/// How many steps of history to keep
+const MAX_HISTORY: usize = 256;
+
+pub(crate) struct HistoryPlugin<S: States> {
+    pub state: S,
+}
+
+impl<S: States> Plugin for HistoryPlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditorHistory>();
+        app.add_system(
+            undo_redo_keys
+                .in_set(EditorSet)
+                .run_if(crate::text::not_editing_text)
+        );
+    }
+}
+
+/// A change to the `Transform` of a single entity
+#[derive(Debug, Clone)]
+pub struct TransformChange {
+    pub entity: Entity,
+    pub before: Transform,
+    pub after: Transform,
+}
+
+/// One undoable step
+#[derive(Debug, Clone, Default)]
+pub struct HistoryStep {
+    pub transforms: Vec<TransformChange>,
+}
+
+impl HistoryStep {
+    pub fn is_empty(&self) -> bool {
+        self.transforms.is_empty()
+    }
+
+    fn apply(&self, world: &mut World, undo: bool) {
+        for change in &self.transforms {
+            if let Some(mut xf) = world.get_mut::<Transform>(change.entity) {
+                *xf = if undo { change.before } else { change.after };
+            }
+        }
+    }
+}
+
+/// The Undo/Redo history
+#[derive(Resource, Default)]
+pub struct EditorHistory {
+    undo: Vec<HistoryStep>,
+    redo: Vec<HistoryStep>,
+}
+
+impl EditorHistory {
+    /// Record a new step (clears the redo stack)
+    ///
+    /// Empty steps are ignored.
+    pub fn push(&mut self, step: HistoryStep) {
+        if step.is_empty() {
+            return;
+        }
+        self.redo.clear();
+        self.undo.push(step);
+        if self.undo.len() > MAX_HISTORY {
+            self.undo.remove(0);
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
+
+/// Undo the last step in the history
+pub fn undo(world: &mut World) {
+    let Some(step) = world.resource_mut::<EditorHistory>().undo.pop() else {
+        return;
+    };
+    step.apply(world, true);
+    world.resource_mut::<EditorHistory>().redo.push(step);
+}
+
+/// Redo the last undone step
+pub fn redo(world: &mut World) {
+    let Some(step) = world.resource_mut::<EditorHistory>().redo.pop() else {
+        return;
+    };
+    step.apply(world, false);
+    world.resource_mut::<EditorHistory>().undo.push(step);
+}
+
+fn undo_redo_keys(world: &mut World) {
+    // TODO: transition to a proper input mgmt framework like LWIM
+    let kbd = world.resource::<Input<KeyCode>>();
+    if !kbd.any_pressed([KeyCode::LControl, KeyCode::RControl]) {
+        return;
+    }
+    let shift = kbd.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+    if kbd.just_pressed(KeyCode::Y) || (shift && kbd.just_pressed(KeyCode::Z)) {
+        redo(world);
+    } else if kbd.just_pressed(KeyCode::Z) {
+        undo(world);
+    }
+}
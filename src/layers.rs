@@ -0,0 +1,585 @@
+//! Editor layers for organizing level content
+//!
+//! Layers are defined in the `EditorLayers` resource, ordered bottom to top.
+//! Entities are assigned to a layer using the `EditorLayer` component.
+//! Each layer can be hidden or locked as a whole, which works just like
+//! `EditorHidden`/`EditorLocked` on each of its entities.
+//!
+//! Optionally, the layer order can drive the Z coordinate of the
+//! (top-level) entities in each layer, so that the 2D draw order is
+//! consistent with the layer order.
+//!
+//! A copy of `EditorLayers` is kept as a component on a regular entity
+//! in the world (like `CameraBookmarks`), so that the layer definitions
+//! get saved together with the level. `EditorLayer` is also registered for
+//! reflection, so layer membership gets saved too.
+//!
+//! Everything is managed from the "Layers" panel.
+
+use crate::crate_prelude::*;
+use crate::selection::Selected;
+use crate::ui::panel::EditorPanelAppExt;
+use crate::ui::tooltip::TooltipText;
+use crate::ui::{UiTextInputActive, UiTextInputOwner};
+
+pub(crate) struct LayersPlugin<S: States> {
+    pub state: S,
+}
+
+impl<S: States> Plugin for LayersPlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.register_type::<EditorLayer>();
+        app.register_type::<EditorLayers>();
+        app.register_type::<EditorLayerDef>();
+        app.init_resource::<EditorLayers>();
+        app.init_resource::<LayerRename>();
+        app.add_editor_panel("Layers", populate_layers_panel);
+        app.add_systems(
+            (
+                enter_layers,
+            ).in_schedule(OnEnter(self.state.clone()))
+        );
+        app.add_systems(
+            (
+                stop_layer_rename,
+                remove_from_all::<LayerHidden, With<LayerHidden>>,
+                remove_from_all::<LayerLocked, With<LayerLocked>>,
+            ).in_schedule(OnExit(self.state.clone()))
+        );
+        app.add_systems(
+            (
+                load_layers,
+                save_layers,
+                apply_layer_flags,
+                drive_layer_z,
+            ).chain().in_set(EditorSet)
+        );
+        app.add_system(
+            layer_rename_input
+                .in_set(EditorSet)
+        );
+        app.add_system(
+            update_layers_panel
+                .in_set(EditorSet)
+                .after(layer_rename_input)
+        );
+    }
+}
+
+/// Assigns an entity to an editor layer
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect, FromReflect)]
+#[reflect(Component)]
+pub struct EditorLayer {
+    /// The `id` of the `EditorLayerDef`
+    pub id: u32,
+}
+
+/// Definition of an editor layer
+#[derive(Debug, Default, Clone, Reflect, FromReflect)]
+pub struct EditorLayerDef {
+    /// Stable identifier, referred to by `EditorLayer`
+    pub id: u32,
+    pub name: String,
+    pub visible: bool,
+    pub locked: bool,
+}
+
+/// All the editor layers, ordered bottom to top
+///
+/// This is the resource the editor works with. A copy is also stored as a
+/// component on an entity in the world, to be saved with the level. If that
+/// component is loaded/replaced (such as when loading a level), the resource
+/// is updated from it.
+#[derive(Resource, Component, Debug, Clone, Reflect, FromReflect)]
+#[reflect(Component)]
+pub struct EditorLayers {
+    pub layers: Vec<EditorLayerDef>,
+    /// Set the Z coordinate of top-level entities according to their layer
+    pub drive_z: bool,
+    /// Z coordinate of the bottom layer
+    pub z_base: f32,
+    /// Z distance between layers
+    ///
+    /// Within a layer, the relative Z order of entities is preserved,
+    /// as long as it fits within this range.
+    pub z_step: f32,
+}
+
+impl Default for EditorLayers {
+    fn default() -> Self {
+        EditorLayers {
+            layers: vec![],
+            drive_z: false,
+            z_base: 0.0,
+            z_step: 100.0,
+        }
+    }
+}
+
+impl EditorLayers {
+    pub fn get(&self, id: u32) -> Option<&EditorLayerDef> {
+        self.layers.iter().find(|l| l.id == id)
+    }
+
+    pub fn get_mut(&mut self, id: u32) -> Option<&mut EditorLayerDef> {
+        self.layers.iter_mut().find(|l| l.id == id)
+    }
+
+    /// Position of the layer in the order (0 is the bottom)
+    pub fn index_of(&self, id: u32) -> Option<usize> {
+        self.layers.iter().position(|l| l.id == id)
+    }
+
+    /// Create a new layer on top of all others, returns its id
+    pub fn add(&mut self, name: impl Into<String>) -> u32 {
+        let id = self.layers.iter().map(|l| l.id + 1).max().unwrap_or(0);
+        self.layers.push(EditorLayerDef {
+            id,
+            name: name.into(),
+            visible: true,
+            locked: false,
+        });
+        id
+    }
+
+    pub fn remove(&mut self, id: u32) {
+        self.layers.retain(|l| l.id != id);
+    }
+
+    /// Move a layer up (towards the top) or down by the given number of places
+    pub fn move_by(&mut self, id: u32, places: isize) {
+        let Some(i) = self.index_of(id) else {
+            return;
+        };
+        let j = (i as isize + places).clamp(0, self.layers.len() as isize - 1) as usize;
+        let layer = self.layers.remove(i);
+        self.layers.insert(j, layer);
+    }
+}
+
+/// Added to entities whose layer is hidden
+#[derive(Component)]
+pub(crate) struct LayerHidden;
+
+/// Added to entities whose layer is locked
+#[derive(Component)]
+pub(crate) struct LayerLocked;
+
+/// The layer whose name is being typed in the panel
+#[derive(Resource, Default)]
+struct LayerRename {
+    id: Option<u32>,
+    /// The name before renaming, for Escape to revert to
+    original: String,
+}
+
+fn enter_layers(
+    mut layers: ResMut<EditorLayers>,
+    q_saved: Query<&EditorLayers>,
+) {
+    *layers = q_saved.iter().next().cloned().unwrap_or_default();
+}
+
+fn load_layers(
+    mut layers: ResMut<EditorLayers>,
+    q_saved: Query<&EditorLayers, Added<EditorLayers>>,
+) {
+    if let Some(saved) = q_saved.iter().next() {
+        *layers = saved.clone();
+    }
+}
+
+fn save_layers(
+    mut commands: Commands,
+    layers: Res<EditorLayers>,
+    mut q_saved: Query<&mut EditorLayers>,
+) {
+    if !layers.is_changed() {
+        return;
+    }
+    if let Some(mut saved) = q_saved.iter_mut().next() {
+        *saved = layers.clone();
+    } else if !layers.layers.is_empty() {
+        commands.spawn((
+            Name::new("Editor Layers"),
+            EditorData,
+            layers.clone(),
+        ));
+    }
+}
+
+fn apply_layer_flags(
+    mut commands: Commands,
+    layers: Res<EditorLayers>,
+    q_member: Query<(Entity, Ref<EditorLayer>, Option<&LayerHidden>, Option<&LayerLocked>)>,
+    mut removed: RemovedComponents<EditorLayer>,
+) {
+    for e in removed.iter() {
+        if let Some(mut e) = commands.get_entity(e) {
+            e.remove::<(LayerHidden, LayerLocked)>();
+        }
+    }
+    let all = layers.is_changed();
+    for (e, layer, hidden, locked) in &q_member {
+        if !all && !layer.is_changed() {
+            continue;
+        }
+        let def = layers.get(layer.id);
+        let want_hidden = def.map(|l| !l.visible).unwrap_or(false);
+        let want_locked = def.map(|l| l.locked).unwrap_or(false);
+        if want_hidden && hidden.is_none() {
+            commands.entity(e).insert(LayerHidden);
+        } else if !want_hidden && hidden.is_some() {
+            commands.entity(e).remove::<LayerHidden>();
+        }
+        if want_locked && locked.is_none() {
+            commands.entity(e).insert(LayerLocked);
+        } else if !want_locked && locked.is_some() {
+            commands.entity(e).remove::<LayerLocked>();
+        }
+    }
+}
+
+fn drive_layer_z(
+    layers: Res<EditorLayers>,
+    mut q_member: Query<(Ref<EditorLayer>, &mut Transform), Without<Parent>>,
+) {
+    if !layers.drive_z || layers.z_step <= 0.0 {
+        return;
+    }
+    let all = layers.is_changed();
+    for (layer, mut xf) in &mut q_member {
+        if !all && !layer.is_changed() {
+            continue;
+        }
+        let Some(i) = layers.index_of(layer.id) else {
+            continue;
+        };
+        // keep the position within the layer's range
+        let offset = (xf.translation.z - layers.z_base).rem_euclid(layers.z_step);
+        let z = layers.z_base + i as f32 * layers.z_step + offset;
+        if xf.translation.z != z {
+            xf.translation.z = z;
+        }
+    }
+}
+
+/// The UI node containing the rows for each layer
+#[derive(Component)]
+struct LayersList;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum LayerAction {
+    ToggleVisible,
+    ToggleLocked,
+    Up,
+    Down,
+    Assign,
+    Rename,
+    Delete,
+}
+
+/// Button that does something to a layer
+#[derive(Component)]
+struct LayerButt {
+    id: u32,
+    action: LayerAction,
+}
+
+/// Buttons at the bottom of the panel
+#[derive(Component, Clone, Copy)]
+enum LayersBarButt {
+    New,
+    Unassign,
+    DriveZ,
+}
+
+fn populate_layers_panel(
+    In(e_contents): In<Entity>,
+    mut commands: Commands,
+    assets: Res<EditorAssets>,
+) {
+    let list = commands.spawn((
+        NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Stretch,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        LayersList,
+    )).id();
+    let bar = commands.spawn((
+        NodeBundle {
+            style: Style {
+                margin: UiRect::top(Val::Px(4.0)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )).id();
+    let butts = [
+        ("New", LayersBarButt::New, "New Layer", "Create a new layer on top of all others."),
+        ("Unassign", LayersBarButt::Unassign, "Unassign Selection", "Remove the selected entities from their layers."),
+        ("Drive Z", LayersBarButt::DriveZ, "Drive Z", "Toggle: set the Z coordinate of entities according to the order of their layers."),
+    ];
+    for (label, butt, title, text) in butts {
+        let e_butt = spawn_text_butt(&mut commands, &*assets, label, title, text);
+        commands.entity(e_butt).insert((
+            butt,
+            ClickBehavior::new().entity_system(layers_bar_handler),
+        ));
+        commands.entity(bar).push_children(&[e_butt]);
+    }
+    commands.entity(e_contents).push_children(&[list, bar]);
+}
+
+fn spawn_text_butt(
+    commands: &mut Commands,
+    assets: &EditorAssets,
+    label: &str,
+    tooltip_title: &str,
+    tooltip_text: &str,
+) -> Entity {
+    let butt = commands.spawn((
+        ButtonBundle {
+            background_color: BackgroundColor(Color::NONE),
+            style: Style {
+                padding: UiRect::all(Val::Px(1.0)),
+                margin: UiRect::right(Val::Px(2.0)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        TooltipText {
+            title: tooltip_title.into(),
+            text: tooltip_text.into(),
+        },
+    )).id();
+    let text = commands.spawn((
+        TextBundle {
+            focus_policy: FocusPolicy::Pass,
+            text: Text::from_section(label, TextStyle {
+                font: assets.font.clone(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        ThemeText::default(),
+    )).id();
+    commands.entity(butt).push_children(&[text]);
+    butt
+}
+
+fn update_layers_panel(
+    mut commands: Commands,
+    assets: Res<EditorAssets>,
+    layers: Res<EditorLayers>,
+    rename: Res<LayerRename>,
+    q_list: Query<Entity, With<LayersList>>,
+    q_added: Query<(), Added<LayersList>>,
+) {
+    if !layers.is_changed() && !rename.is_changed() && q_added.is_empty() {
+        return;
+    }
+    for e_list in &q_list {
+        commands.entity(e_list).despawn_descendants();
+        // top layer first
+        for layer in layers.layers.iter().rev() {
+            let row = commands.spawn((
+                NodeBundle {
+                    style: Style {
+                        align_items: AlignItems::Center,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            )).id();
+            let butts = [
+                (if layer.visible { "[V]" } else { "[ ]" }, LayerAction::ToggleVisible, "Visible", "Show/hide all entities in this layer."),
+                (if layer.locked { "[L]" } else { "[ ]" }, LayerAction::ToggleLocked, "Locked", "Lock/unlock all entities in this layer."),
+                ("^", LayerAction::Up, "Move Up", "Move this layer above the next one."),
+                ("v", LayerAction::Down, "Move Down", "Move this layer below the previous one."),
+                ("+", LayerAction::Assign, "Assign Selection", "Move the selected entities into this layer."),
+                ("R", LayerAction::Rename, "Rename", "Type a new name. Press Enter when done."),
+                ("x", LayerAction::Delete, "Delete", "Delete this layer. Its entities are kept, without a layer."),
+            ];
+            for (label, action, title, text) in butts {
+                let butt = spawn_text_butt(&mut commands, &*assets, label, title, text);
+                commands.entity(butt).insert((
+                    LayerButt {
+                        id: layer.id,
+                        action,
+                    },
+                    ClickBehavior::new().entity_system(layer_butt_handler),
+                ));
+                commands.entity(row).push_children(&[butt]);
+            }
+            let renaming = rename.id == Some(layer.id);
+            let label = commands.spawn((
+                TextBundle {
+                    style: Style {
+                        margin: UiRect::left(Val::Px(4.0)),
+                        ..Default::default()
+                    },
+                    text: Text::from_section(
+                        if renaming { format!("{}_", layer.name) } else { layer.name.clone() },
+                        TextStyle {
+                            font: assets.font.clone(),
+                            ..Default::default()
+                        }
+                    ),
+                    ..Default::default()
+                },
+                ThemeText {
+                    color: if renaming {
+                        |t| t.colors.text_highlight
+                    } else {
+                        |t| t.colors.text
+                    },
+                    ..Default::default()
+                },
+            )).id();
+            commands.entity(row).push_children(&[label]);
+            commands.entity(e_list).push_children(&[row]);
+        }
+    }
+}
+
+fn layers_bar_handler(
+    In(entity): In<Entity>,
+    mut commands: Commands,
+    mut layers: ResMut<EditorLayers>,
+    q_butt: Query<&LayersBarButt>,
+    q_selected: Query<Entity, With<Selected>>,
+) {
+    let Ok(butt) = q_butt.get(entity) else { return; };
+    match butt {
+        LayersBarButt::New => {
+            // name it after the id, which is unique (the count is not, after a delete)
+            let id = layers.add("");
+            if let Some(layer) = layers.get_mut(id) {
+                layer.name = format!("Layer {}", id);
+            }
+        }
+        LayersBarButt::Unassign => {
+            for e in &q_selected {
+                commands.entity(e).remove::<EditorLayer>();
+            }
+        }
+        LayersBarButt::DriveZ => {
+            layers.drive_z = !layers.drive_z;
+        }
+    }
+}
+
+fn layer_butt_handler(
+    In(entity): In<Entity>,
+    mut commands: Commands,
+    mut layers: ResMut<EditorLayers>,
+    mut rename: ResMut<LayerRename>,
+    mut text_input: ResMut<UiTextInputActive>,
+    q_butt: Query<&LayerButt>,
+    q_selected: Query<Entity, With<Selected>>,
+    q_member: Query<(Entity, &EditorLayer)>,
+) {
+    let Ok(butt) = q_butt.get(entity) else { return; };
+    let id = butt.id;
+    if butt.action != LayerAction::Rename && rename.id.is_some() {
+        rename.id = None;
+        text_input.release(UiTextInputOwner::LayerRename);
+    }
+    match butt.action {
+        LayerAction::ToggleVisible => {
+            if let Some(layer) = layers.get_mut(id) {
+                layer.visible = !layer.visible;
+            }
+        }
+        LayerAction::ToggleLocked => {
+            if let Some(layer) = layers.get_mut(id) {
+                layer.locked = !layer.locked;
+            }
+        }
+        LayerAction::Up => layers.move_by(id, 1),
+        LayerAction::Down => layers.move_by(id, -1),
+        LayerAction::Assign => {
+            for e in &q_selected {
+                commands.entity(e).insert(EditorLayer { id });
+            }
+        }
+        LayerAction::Rename => {
+            rename.id = Some(id);
+            rename.original = layers.get(id).map(|l| l.name.clone()).unwrap_or_default();
+            text_input.take(UiTextInputOwner::LayerRename);
+        }
+        LayerAction::Delete => {
+            layers.remove(id);
+            for (e, layer) in &q_member {
+                if layer.id == id {
+                    commands.entity(e).remove::<EditorLayer>();
+                }
+            }
+        }
+    }
+}
+
+fn layer_rename_input(
+    kbd: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    mut evr_char: EventReader<ReceivedCharacter>,
+    mut layers: ResMut<EditorLayers>,
+    mut rename: ResMut<LayerRename>,
+    mut text_input: ResMut<UiTextInputActive>,
+    q_butt: Query<(&Interaction, &LayerButt)>,
+) {
+    // TODO: transition to a proper input mgmt framework like LWIM
+    // read the characters every frame, so that anything typed before
+    // renaming started is thrown away
+    let chars: Vec<char> = evr_char.iter().map(|ev| ev.char).collect();
+    let Some(id) = rename.id else {
+        return;
+    };
+    // something else (such as a widget) took the text input
+    let lost = !text_input.is_owner(UiTextInputOwner::LayerRename);
+    // clicking anywhere other than the layer's name stops renaming
+    let clicked_away = mouse.just_pressed(MouseButton::Left) && !q_butt.iter().any(|(interaction, butt)| {
+        *interaction == Interaction::Clicked && butt.action == LayerAction::Rename && butt.id == id
+    });
+    let cancel = kbd.just_pressed(KeyCode::Escape);
+    let done = cancel || lost || clicked_away || kbd.any_just_pressed([KeyCode::Return, KeyCode::NumpadEnter]);
+    let back = !done && kbd.just_pressed(KeyCode::Back);
+    let typed: String = chars.into_iter()
+        .filter(|c| !c.is_control() && !done)
+        .collect();
+    if !done && !back && typed.is_empty() {
+        return;
+    }
+    let Some(layer) = layers.get_mut(id) else {
+        rename.id = None;
+        text_input.release(UiTextInputOwner::LayerRename);
+        return;
+    };
+    if back {
+        layer.name.pop();
+    }
+    layer.name.push_str(&typed);
+    if cancel {
+        layer.name = rename.original.clone();
+    }
+    if done {
+        if layer.name.is_empty() {
+            layer.name = format!("Layer {}", id);
+        }
+        rename.id = None;
+        text_input.release(UiTextInputOwner::LayerRename);
+    }
+}
+
+fn stop_layer_rename(
+    mut rename: ResMut<LayerRename>,
+    mut text_input: ResMut<UiTextInputActive>,
+) {
+    rename.id = None;
+    text_input.release(UiTextInputOwner::LayerRename);
+}
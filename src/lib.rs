@@ -6,20 +6,45 @@ pub mod tilemap;
 
 // Non-optional modules
 pub mod sprite;
+pub mod atlas;
+pub mod text;
+pub mod mesh;
 
 // General editor framework modules
 pub mod camera;
 pub mod tool;
 pub mod selection;
+pub mod selectable;
+pub mod transform;
+pub mod history;
+pub mod arrange;
+pub mod entities;
+pub mod minimap;
+pub mod grid;
+pub mod lock;
+pub mod layers;
+pub mod outliner;
+pub mod settings;
+
+pub use crate::ui::theme;
+pub use crate::ui::widget;
 
 // Internal support modules
 mod assets;
 mod ui;
 mod misc;
+mod spatial;
+mod clipboard;
 
 /// Public prelude
 pub mod prelude {
     pub use crate::EditorPlugin;
+    pub use crate::assets::ShowEditorAssetErrors;
+    pub use crate::selectable::{SelectablePlugin, SelectableProvider};
+    pub use crate::assets::EditorAssets;
+    pub use crate::ui::panel::{EditorPanelAppExt, PanelRegistry, PanelSizeLimits};
+    pub use crate::ui::theme::{EditorTheme, ThemeBackground, ThemeText};
+    pub use crate::ui::widget::WidgetChanged;
 }
 
 /// Common prelude for internal use
@@ -32,7 +57,9 @@ mod crate_prelude {
     pub use iyes_bevy_extras::prelude::*;
     pub use crate::tool::*;
     pub use crate::assets::EditorAssets;
+    pub use crate::ui::theme::{EditorTheme, ThemeBackground, ThemePadding, ThemeText, ThemeZIndex};
     pub use crate::EditorCleanup;
+    pub use crate::EditorData;
     pub use crate::EditorSet;
     pub(crate) use crate::EditorFlush;
 }
@@ -44,6 +71,14 @@ mod crate_prelude {
 #[derive(Component)]
 pub struct EditorCleanup;
 
+/// Marker for entities that hold the editor's own data, saved with the level
+/// (such as camera bookmarks and layers)
+///
+/// They are not part of the level itself, so they are not shown in the Outliner.
+#[derive(Component, Debug, Default, Clone, Reflect, FromReflect)]
+#[reflect(Component)]
+pub struct EditorData;
+
 /// Add this to your App to integrate the Iyes2D Editor!
 ///
 /// This is the main API of this crate, everything else starts from here.
@@ -81,15 +116,44 @@ pub struct EditorCleanup;
 /// ## TL;DR: Iyes2D Editor setup/integration instructions:
 ///
 /// 1. Be sure to copy the editor's asset files into your assets folder!
+///    (Or enable the `embedded_assets` cargo feature.)
 /// 2. Create app states for the editor to run in.
-/// 3. Add this plugin to your App, specifying the states you created.
+/// 3. Add this plugin to your App, specifying the states you created
+///    (`EditorPlugin::new`).
 /// 4. Add some system to your app, that transitions into the editor loading
 ///    state, whenever you want to enter the editor.
+///
+/// If the editor's assets fail to load (such as if a file is missing from
+/// the asset pack), the editor logs which asset keys failed. By default, it
+/// also shows them on screen (see `ShowEditorAssetErrors`). It stays in the
+/// loading state, unless you set an *asset failure state* to go to instead
+/// (such as the state you were in before entering the editor).
+///
+/// The editor remembers its panel layout and preferences in a settings file.
+/// Insert a `settings::EditorSettingsDir` resource to choose where.
 pub struct EditorPlugin<S: States> {
     pub asset_load_state: S,
+    /// Where to go if the editor's assets fail to load (`None`: stay in `asset_load_state`)
+    pub asset_failure_state: Option<S>,
     pub editor_state: S,
 }
 
+impl<S: States> EditorPlugin<S> {
+    pub fn new(asset_load_state: S, editor_state: S) -> Self {
+        EditorPlugin {
+            asset_load_state,
+            asset_failure_state: None,
+            editor_state,
+        }
+    }
+
+    /// Go to this state if the editor's assets fail to load
+    pub fn with_asset_failure_state(mut self, state: S) -> Self {
+        self.asset_failure_state = Some(state);
+        self
+    }
+}
+
 /// Set for all editor systems
 ///
 /// If you need additional configuration (beyond just running in the given state),
@@ -103,6 +167,7 @@ pub(crate) struct EditorFlush;
 
 impl<S: States> Plugin for EditorPlugin<S> {
     fn build(&self, app: &mut App) {
+        app.register_type::<EditorData>();
         app.configure_set(
             EditorSet
                 .run_if(in_state(self.editor_state.clone()))
@@ -118,6 +183,7 @@ impl<S: States> Plugin for EditorPlugin<S> {
         app.add_state::<crate::tool::Tool>();
         app.add_plugin(crate::assets::EditorAssetsPlugin {
             asset_load_state: self.asset_load_state.clone(),
+            asset_failure_state: self.asset_failure_state.clone(),
             editor_state: self.editor_state.clone(),
         });
         app.add_plugin(crate::camera::CameraPlugin {
@@ -129,9 +195,48 @@ impl<S: States> Plugin for EditorPlugin<S> {
         app.add_plugin(crate::ui::EditorUiPlugin {
             state: self.editor_state.clone()
         });
+        app.add_plugin(crate::history::HistoryPlugin {
+            state: self.editor_state.clone()
+        });
+        app.add_plugin(crate::entities::EntityOpsPlugin {
+            state: self.editor_state.clone()
+        });
+        app.add_plugin(crate::transform::TransformEditorPlugin {
+            state: self.editor_state.clone()
+        });
+        app.add_plugin(crate::grid::GridPlugin {
+            state: self.editor_state.clone()
+        });
+        app.add_plugin(crate::minimap::MinimapPlugin {
+            state: self.editor_state.clone()
+        });
+        app.add_plugin(crate::lock::LockPlugin {
+            state: self.editor_state.clone()
+        });
+        app.add_plugin(crate::layers::LayersPlugin {
+            state: self.editor_state.clone()
+        });
+        app.add_plugin(crate::outliner::OutlinerPlugin {
+            state: self.editor_state.clone()
+        });
+        app.add_plugin(crate::settings::SettingsPlugin {
+            state: self.editor_state.clone()
+        });
+        app.add_plugin(crate::selectable::SelectablePickingPlugin {
+            state: self.editor_state.clone()
+        });
         app.add_plugin(crate::sprite::SpriteEditorPlugin {
             state: self.editor_state.clone()
         });
+        app.add_plugin(crate::atlas::AtlasEditorPlugin {
+            state: self.editor_state.clone()
+        });
+        app.add_plugin(crate::text::TextEditorPlugin {
+            state: self.editor_state.clone()
+        });
+        app.add_plugin(crate::mesh::MeshEditorPlugin {
+            state: self.editor_state.clone()
+        });
         #[cfg(feature = "bevy_ecs_tilemap")]
         app.add_plugin(crate::tilemap::TilemapEditorPlugin {
             state: self.editor_state.clone()
@@ -0,0 +1,154 @@
+//! Locking and hiding entities while editing
+//!
+//! `EditorLocked` entities cannot be picked (selected with the mouse), and
+//! so cannot be moved either. `EditorHidden` entities (and their children)
+//! are not rendered while in the editor, and cannot be picked either. Their
+//! `Visibility` is left alone, so that saving the level doesn't hide them
+//! in the game.
+//!
+//! Both components are registered for reflection, so they are saved with
+//! the level, and are toggled from the Edit menu (for the selection) or
+//! from the Outliner panel.
+
+use bevy::render::view::VisibilitySystems;
+use crate::crate_prelude::*;
+use crate::layers::{LayerHidden, LayerLocked};
+use crate::selection::Selected;
+
+pub(crate) struct LockPlugin<S: States> {
+    pub state: S,
+}
+
+impl<S: States> Plugin for LockPlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.register_type::<EditorLocked>();
+        app.register_type::<EditorHidden>();
+        app.add_system(
+            apply_hidden
+                .in_set(EditorSet)
+                .in_base_set(CoreSet::PostUpdate)
+                .after(VisibilitySystems::CheckVisibility)
+        );
+        app.add_system(
+            deselect_unpickable
+                .in_set(EditorSet)
+        );
+    }
+}
+
+/// Entities with this component cannot be picked or moved in the editor
+#[derive(Component, Debug, Default, Clone, Reflect, FromReflect)]
+#[reflect(Component)]
+pub struct EditorLocked;
+
+/// Entities with this component are invisible and cannot be picked while in the editor
+#[derive(Component, Debug, Default, Clone, Reflect, FromReflect)]
+#[reflect(Component)]
+pub struct EditorHidden;
+
+/// Query filter for entities that must not be picked
+///
+/// Anything that lets the user select entities in the world (clicking,
+/// marquee, etc.) must skip entities matching this. This includes entities
+/// in hidden or locked editor layers.
+pub type Unpickable = Or<(
+    With<EditorLocked>,
+    With<EditorHidden>,
+    With<LayerLocked>,
+    With<LayerHidden>,
+    With<EditorCleanup>,
+)>;
+
+/// Override the computed visibility, which Bevy recomputes every frame
+fn apply_hidden(
+    q_hidden: Query<Entity, Or<(With<EditorHidden>, With<LayerHidden>)>>,
+    q_children: Query<&Children>,
+    mut q_vis: Query<&mut ComputedVisibility>,
+) {
+    for e in &q_hidden {
+        for e in std::iter::once(e).chain(q_children.iter_descendants(e)) {
+            if let Ok(mut vis) = q_vis.get_mut(e) {
+                *vis = ComputedVisibility::INVISIBLE;
+            }
+        }
+    }
+}
+
+/// Locked/hidden entities must not stay selected (and movable)
+fn deselect_unpickable(
+    mut commands: Commands,
+    q: Query<Entity, (
+        With<Selected>,
+        Or<(Added<EditorLocked>, Added<EditorHidden>, Added<LayerLocked>, Added<LayerHidden>)>,
+    )>,
+) {
+    for e in &q {
+        commands.add(move |world: &mut World| {
+            crate::selection::deselect_entity(world, e);
+        });
+    }
+}
+
+/// Lock or unlock an entity
+pub fn set_locked(world: &mut World, e: Entity, locked: bool) {
+    let Some(mut entity) = world.get_entity_mut(e) else {
+        return;
+    };
+    if locked {
+        entity.insert(EditorLocked);
+    } else {
+        entity.remove::<EditorLocked>();
+    }
+}
+
+/// Hide or unhide an entity
+pub fn set_hidden(world: &mut World, e: Entity, hidden: bool) {
+    let Some(mut entity) = world.get_entity_mut(e) else {
+        return;
+    };
+    if hidden {
+        entity.insert(EditorHidden);
+    } else {
+        entity.remove::<EditorHidden>();
+    }
+}
+
+fn selected(world: &mut World) -> Vec<Entity> {
+    world.query_filtered::<Entity, With<Selected>>()
+        .iter(world)
+        .collect()
+}
+
+/// Lock all selected entities
+pub fn lock_selected(world: &mut World) {
+    for e in selected(world) {
+        set_locked(world, e, true);
+    }
+}
+
+/// Hide all selected entities
+pub fn hide_selected(world: &mut World) {
+    for e in selected(world) {
+        set_hidden(world, e, true);
+    }
+}
+
+/// Unlock all locked entities
+pub fn unlock_all(world: &mut World) {
+    let locked: Vec<Entity> = world.query_filtered::<Entity, With<EditorLocked>>()
+        .iter(world)
+        .collect();
+    for e in locked {
+        set_locked(world, e, false);
+    }
+}
+
+/// Unhide all hidden entities
+pub fn unhide_all(world: &mut World) {
+    let hidden: Vec<Entity> = world.query_filtered::<Entity, With<EditorHidden>>()
+        .iter(world)
+        .collect();
+    for e in hidden {
+        set_hidden(world, e, false);
+    }
+}
@@ -0,0 +1,184 @@
+//! Picking of 2D meshes (`MaterialMesh2dBundle`)
+//!
+//! The bounding box of the mesh is used for quickly rejecting meshes that are
+//! not under the cursor, and then the mesh's triangles are tested precisely.
+//! Meshes that are not triangle lists/strips are picked by their bounding box.
+
+use bevy::asset::HandleId;
+use bevy::ecs::query::ROQueryItem;
+use bevy::ecs::system::SystemParamItem;
+use bevy::render::mesh::{Indices, PrimitiveTopology, VertexAttributeValues};
+use bevy::sprite::Mesh2dHandle;
+
+use crate::crate_prelude::*;
+use crate::selectable::{SelectableIndex, SelectableIndexSet, SelectablePlugin, SelectableProvider};
+
+pub(crate) struct MeshEditorPlugin<S: States> {
+    pub state: S,
+}
+
+impl<S: States> Plugin for MeshEditorPlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MeshShapes>();
+        app.add_plugin(SelectablePlugin::<Mesh2dProvider>::default());
+        app.add_system(
+            update_mesh_shapes
+                .in_set(EditorSet)
+                .before(SelectableIndexSet)
+        );
+        // meshes can be removed while not in the editor, too
+        app.add_system(forget_removed_meshes);
+    }
+}
+
+/// The shape of a mesh, in its local space
+struct MeshShape {
+    rect: Rect,
+    triangles: Vec<[Vec2; 3]>,
+}
+
+/// Cache of mesh shapes, so we don't have to process the vertex data every time
+/// an entity moves
+#[derive(Resource, Default)]
+pub struct MeshShapes {
+    shapes: HashMap<HandleId, MeshShape>,
+}
+
+/// Makes `Mesh2dHandle` entities (such as `MaterialMesh2dBundle`) selectable
+pub struct Mesh2dProvider;
+
+impl SelectableProvider for Mesh2dProvider {
+    type Query = &'static Mesh2dHandle;
+    type Changed = Changed<Mesh2dHandle>;
+    type Param = Res<'static, MeshShapes>;
+
+    fn bounds(
+        handle: ROQueryItem<'_, Self::Query>,
+        shapes: &SystemParamItem<Self::Param>,
+    ) -> Option<Rect> {
+        shapes.shapes.get(&handle.0.id()).map(|shape| shape.rect)
+    }
+
+    fn hit_test(
+        handle: ROQueryItem<'_, Self::Query>,
+        shapes: &SystemParamItem<Self::Param>,
+        _bounds: Rect,
+        local: Vec2,
+    ) -> bool {
+        let Some(shape) = shapes.shapes.get(&handle.0.id()) else {
+            return false;
+        };
+        shape.triangles.iter().any(|&[a, b, c]| {
+            let d1 = (local - b).perp_dot(a - b);
+            let d2 = (local - c).perp_dot(b - c);
+            let d3 = (local - a).perp_dot(c - a);
+            let has_neg = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+            let has_pos = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+            !(has_neg && has_pos)
+        })
+    }
+}
+
+impl MeshShape {
+    /// Returns `None` if the mesh does not have 2D/3D float positions
+    fn from_mesh(mesh: &Mesh) -> Option<MeshShape> {
+        let positions: Vec<Vec2> = match mesh.attribute(Mesh::ATTRIBUTE_POSITION)? {
+            VertexAttributeValues::Float32x3(v) => v.iter().map(|p| Vec2::new(p[0], p[1])).collect(),
+            VertexAttributeValues::Float32x2(v) => v.iter().map(|p| Vec2::new(p[0], p[1])).collect(),
+            _ => return None,
+        };
+        if positions.is_empty() {
+            return None;
+        }
+        let (min, max) = positions.iter().fold(
+            (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+            |(min, max), p| (min.min(*p), max.max(*p)),
+        );
+
+        let indices: Vec<usize> = match mesh.indices() {
+            Some(Indices::U16(i)) => i.iter().map(|i| *i as usize).collect(),
+            Some(Indices::U32(i)) => i.iter().map(|i| *i as usize).collect(),
+            None => (0..positions.len()).collect(),
+        };
+        let vertex = |i: usize| positions.get(indices[i]).copied();
+        let mut triangles = vec![];
+        match mesh.primitive_topology() {
+            PrimitiveTopology::TriangleList => {
+                for i in (0..indices.len() / 3).map(|t| t * 3) {
+                    if let (Some(a), Some(b), Some(c)) = (vertex(i), vertex(i + 1), vertex(i + 2)) {
+                        triangles.push([a, b, c]);
+                    }
+                }
+            }
+            PrimitiveTopology::TriangleStrip => {
+                for i in 0..indices.len().saturating_sub(2) {
+                    if let (Some(a), Some(b), Some(c)) = (vertex(i), vertex(i + 1), vertex(i + 2)) {
+                        triangles.push([a, b, c]);
+                    }
+                }
+            }
+            // not something with an area; just use the bounding box
+            _ => {
+                triangles.push([min, Vec2::new(max.x, min.y), max]);
+                triangles.push([min, max, Vec2::new(min.x, max.y)]);
+            }
+        }
+
+        // with no area, the hit test would pass anywhere
+        triangles.retain(|&[a, b, c]| (b - a).perp_dot(c - a).abs() > f32::EPSILON);
+
+        Some(MeshShape {
+            rect: Rect { min, max },
+            triangles,
+        })
+    }
+}
+
+fn forget_removed_meshes(
+    mut shapes: ResMut<MeshShapes>,
+    mut evr_mesh: EventReader<AssetEvent<Mesh>>,
+) {
+    for ev in evr_mesh.iter() {
+        if let AssetEvent::Removed { handle } = ev {
+            shapes.shapes.remove(&handle.id());
+        }
+    }
+}
+
+fn update_mesh_shapes(
+    mut shapes: ResMut<MeshShapes>,
+    mut index: ResMut<SelectableIndex>,
+    meshes: Res<Assets<Mesh>>,
+    mut evr_mesh: EventReader<AssetEvent<Mesh>>,
+    q_changed: Query<&Mesh2dHandle, Changed<Mesh2dHandle>>,
+    q_mesh: Query<(Entity, &Mesh2dHandle)>,
+) {
+    // mesh data changed: recompute the shapes and update everything using them
+    let mut modified = HashSet::default();
+    for ev in evr_mesh.iter() {
+        match ev {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } | AssetEvent::Removed { handle } => {
+                shapes.shapes.remove(&handle.id());
+                modified.insert(handle.id());
+            }
+        }
+    }
+    let mut handles: Vec<&Handle<Mesh>> = q_changed.iter().map(|h| &h.0).collect();
+    if !modified.is_empty() {
+        for (e, handle) in &q_mesh {
+            if modified.contains(&handle.0.id()) {
+                index.invalidate(e);
+                handles.push(&handle.0);
+            }
+        }
+    }
+
+    for handle in handles {
+        if shapes.shapes.contains_key(&handle.id()) {
+            continue;
+        }
+        if let Some(shape) = meshes.get(handle).and_then(MeshShape::from_mesh) {
+            shapes.shapes.insert(handle.id(), shape);
+        }
+    }
+}
@@ -0,0 +1,315 @@
+//! Minimap / overview panel
+//!
+//! A secondary orthographic camera renders the world into a texture, which
+//! is displayed inside a panel. The area visible in the main editor camera
+//! is outlined on top of it, and clicking/dragging on the minimap moves the
+//! editor camera there.
+
+use bevy::core_pipeline::clear_color::ClearColorConfig;
+use bevy::render::camera::RenderTarget;
+use bevy::render::render_resource::{Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages};
+use bevy::window::PrimaryWindow;
+
+use crate::crate_prelude::*;
+use crate::camera::{EditorAuxCamera, EditorCamera};
+use crate::ui::panel::EditorPanelAppExt;
+
+/// Size (in pixels) of the minimap texture and its on-screen display
+const MINIMAP_SIZE: Vec2 = Vec2::new(192.0, 192.0);
+
+/// How often to recompute the area of the world shown on the minimap
+const MINIMAP_FIT_INTERVAL: Duration = Duration::from_millis(500);
+
+pub(crate) struct MinimapPlugin<S: States> {
+    pub state: S,
+}
+
+impl<S: States> Plugin for MinimapPlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.add_editor_panel("Minimap", populate_minimap_panel);
+        app.add_systems(
+            (
+                setup_minimap,
+            ).in_schedule(OnEnter(self.state.clone()))
+        );
+        app.add_systems(
+            (
+                minimap_fit_view,
+                minimap_click.after(crate::camera::WorldCursorSet).before(crate::camera::CameraControlSet),
+                minimap_frustum.after(crate::camera::CameraControlSet),
+            ).in_set(EditorSet)
+        );
+    }
+}
+
+/// Marker for the camera that renders the minimap
+#[derive(Component)]
+pub struct MinimapCamera;
+
+/// Marker for the UI image node displaying the minimap
+#[derive(Component)]
+struct MinimapViewport;
+
+/// Marker for the UI node outlining the editor camera's visible area
+#[derive(Component)]
+struct MinimapFrustum;
+
+/// The render target for the minimap camera; kept around between editor sessions
+#[derive(Resource)]
+struct MinimapImage(Handle<Image>);
+
+fn setup_minimap(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    minimap_image: Option<Res<MinimapImage>>,
+) {
+    let handle = if let Some(minimap_image) = minimap_image {
+        minimap_image.0.clone()
+    } else {
+        let size = Extent3d {
+            width: MINIMAP_SIZE.x as u32,
+            height: MINIMAP_SIZE.y as u32,
+            depth_or_array_layers: 1,
+        };
+        let mut image = Image {
+            texture_descriptor: TextureDescriptor {
+                label: Some("iyes2d_editor_minimap"),
+                size,
+                dimension: TextureDimension::D2,
+                format: TextureFormat::Bgra8UnormSrgb,
+                mip_level_count: 1,
+                sample_count: 1,
+                usage: TextureUsages::TEXTURE_BINDING
+                    | TextureUsages::COPY_DST
+                    | TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            },
+            ..Default::default()
+        };
+        // fill with zeroes
+        image.resize(size);
+        let handle = images.add(image);
+        commands.insert_resource(MinimapImage(handle.clone()));
+        handle
+    };
+
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera {
+                order: -1,
+                target: RenderTarget::Image(handle),
+                ..Default::default()
+            },
+            camera_2d: Camera2d {
+                clear_color: ClearColorConfig::Custom(Color::rgb(0.1, 0.1, 0.1)),
+            },
+            ..Default::default()
+        },
+        UiCameraConfig {
+            show_ui: false,
+        },
+        MinimapCamera,
+        EditorAuxCamera,
+        EditorCleanup,
+    ));
+}
+
+fn populate_minimap_panel(
+    In(e_contents): In<Entity>,
+    mut commands: Commands,
+    minimap_image: Res<MinimapImage>,
+) {
+    let handle = minimap_image.0.clone();
+    let viewport = commands.spawn((
+        ImageBundle {
+            style: Style {
+                size: Size::new(Val::Px(MINIMAP_SIZE.x), Val::Px(MINIMAP_SIZE.y)),
+                overflow: Overflow::Hidden,
+                ..Default::default()
+            },
+            image: UiImage::new(handle),
+            ..Default::default()
+        },
+        Interaction::default(),
+        MinimapViewport,
+    )).id();
+    let frustum = commands.spawn((
+        NodeBundle {
+            focus_policy: FocusPolicy::Pass,
+            style: Style {
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+            visibility: Visibility::Hidden,
+            ..Default::default()
+        },
+        MinimapFrustum,
+    )).id();
+    // outline the rectangle using a thin node along each edge
+    let edges = [
+        (UiRect { left: Val::Px(0.0), top: Val::Px(0.0), ..Default::default() }, Size::new(Val::Percent(100.0), Val::Px(1.0))),
+        (UiRect { left: Val::Px(0.0), bottom: Val::Px(0.0), ..Default::default() }, Size::new(Val::Percent(100.0), Val::Px(1.0))),
+        (UiRect { left: Val::Px(0.0), top: Val::Px(0.0), ..Default::default() }, Size::new(Val::Px(1.0), Val::Percent(100.0))),
+        (UiRect { right: Val::Px(0.0), top: Val::Px(0.0), ..Default::default() }, Size::new(Val::Px(1.0), Val::Percent(100.0))),
+    ];
+    for (position, size) in edges {
+        let edge = commands.spawn(
+            NodeBundle {
+                focus_policy: FocusPolicy::Pass,
+                background_color: BackgroundColor(Color::WHITE),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position,
+                    size,
+                    ..Default::default()
+                },
+                ..Default::default()
+            }
+        ).id();
+        commands.entity(frustum).push_children(&[edge]);
+    }
+    commands.entity(viewport).push_children(&[frustum]);
+    commands.entity(e_contents).push_children(&[viewport]);
+}
+
+/// Zoom the minimap camera to fit everything that is visible in the world
+fn minimap_fit_view(
+    time: Res<Time>,
+    mut timer: Local<Option<Timer>>,
+    q_visible: Query<
+        (&GlobalTransform, &ComputedVisibility),
+        (Without<Camera>, Without<Node>, Without<EditorCleanup>),
+    >,
+    mut q_camera: Query<&mut Transform, With<MinimapCamera>>,
+) {
+    // run immediately the first time, then periodically
+    if let Some(timer) = &mut *timer {
+        timer.tick(time.delta());
+        if !timer.just_finished() {
+            return;
+        }
+    } else {
+        *timer = Some(Timer::new(MINIMAP_FIT_INTERVAL, TimerMode::Repeating));
+    }
+
+    let Ok(mut xf_cam) = q_camera.get_single_mut() else {
+        return;
+    };
+
+    // PERF: this iterates over everything; that's why it runs on a timer
+    let mut min = Vec2::splat(f32::MAX);
+    let mut max = Vec2::splat(f32::MIN);
+    for (xf, vis) in &q_visible {
+        if !vis.is_visible_in_hierarchy() {
+            continue;
+        }
+        let pos = xf.translation().truncate();
+        min = min.min(pos);
+        max = max.max(pos);
+    }
+    if min.x > max.x || min.y > max.y {
+        return;
+    }
+
+    // add some margin, because we only know the entities' origins
+    let size = (max - min).max(Vec2::splat(1.0)) * 1.25 + Vec2::splat(64.0);
+    let scale = (size / MINIMAP_SIZE).max_element();
+    let center = (min + max) / 2.0;
+    xf_cam.translation.x = center.x;
+    xf_cam.translation.y = center.y;
+    xf_cam.scale = Vec3::new(scale, scale, 1.0);
+}
+
+/// Clicking or dragging on the minimap moves the editor camera
+fn minimap_click(
+    q_windows: Query<&Window, With<PrimaryWindow>>,
+    q_viewport: Query<(&Interaction, &Node, &GlobalTransform), With<MinimapViewport>>,
+    q_minimap_cam: Query<(&Camera, &GlobalTransform), With<MinimapCamera>>,
+    mut q_editor_cam: Query<&mut Transform, With<EditorCamera>>,
+) {
+    let Ok((interaction, node, xf_node)) = q_viewport.get_single() else {
+        return;
+    };
+    if *interaction != Interaction::Clicked {
+        return;
+    }
+    let Ok(window) = q_windows.get_single() else {
+        return;
+    };
+    let Some(cursor) = window.cursor_position() else {
+        return;
+    };
+    let Ok((camera, xf_camera)) = q_minimap_cam.get_single() else {
+        return;
+    };
+
+    // UI coordinates have the origin at the top left,
+    // camera viewport coordinates at the bottom left
+    let cursor_ui = Vec2::new(cursor.x, window.height() - cursor.y);
+    let node_min = xf_node.translation().truncate() - node.size() / 2.0;
+    let local = cursor_ui - node_min;
+    let viewport_pos = Vec2::new(local.x, node.size().y - local.y) * MINIMAP_SIZE / node.size();
+
+    if let Some(ray) = camera.viewport_to_world(xf_camera, viewport_pos) {
+        let mut xf_editor = q_editor_cam.single_mut();
+        xf_editor.translation.x = ray.origin.x;
+        xf_editor.translation.y = ray.origin.y;
+    }
+}
+
+/// Outline the area visible in the editor camera on top of the minimap
+fn minimap_frustum(
+    q_editor_cam: Query<(&Camera, &GlobalTransform), With<EditorCamera>>,
+    q_minimap_cam: Query<(&Camera, &GlobalTransform), With<MinimapCamera>>,
+    q_viewport: Query<&Node, With<MinimapViewport>>,
+    mut q_frustum: Query<(&mut Style, &mut Visibility), With<MinimapFrustum>>,
+) {
+    let Ok((mut style, mut visibility)) = q_frustum.get_single_mut() else {
+        return;
+    };
+    let (Ok((editor_cam, xf_editor)), Ok((minimap_cam, xf_minimap)), Ok(node)) = (
+        q_editor_cam.get_single(),
+        q_minimap_cam.get_single(),
+        q_viewport.get_single(),
+    ) else {
+        return;
+    };
+    let Some(editor_size) = editor_cam.logical_viewport_size() else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    // project the corners of the editor camera's view onto the minimap;
+    // if the camera is rotated, we outline the bounding box
+    let corners = [
+        Vec2::new(0.0, 0.0),
+        Vec2::new(editor_size.x, 0.0),
+        Vec2::new(0.0, editor_size.y),
+        Vec2::new(editor_size.x, editor_size.y),
+    ];
+    let mut min = Vec2::splat(f32::MAX);
+    let mut max = Vec2::splat(f32::MIN);
+    for corner in corners {
+        let Some(projected) = editor_cam.viewport_to_world(xf_editor, corner)
+            .and_then(|ray| minimap_cam.world_to_viewport(xf_minimap, ray.origin.truncate().extend(0.0)))
+        else {
+            *visibility = Visibility::Hidden;
+            return;
+        };
+        min = min.min(projected);
+        max = max.max(projected);
+    }
+
+    let scale = node.size() / MINIMAP_SIZE;
+    style.position = UiRect {
+        left: Val::Px(min.x * scale.x),
+        top: Val::Px((MINIMAP_SIZE.y - max.y) * scale.y),
+        right: Val::Auto,
+        bottom: Val::Auto,
+    };
+    style.size = Size::new(
+        Val::Px((max.x - min.x) * scale.x),
+        Val::Px((max.y - min.y) * scale.y),
+    );
+    *visibility = Visibility::Inherited;
+}
@@ -0,0 +1,202 @@
+//! Outliner panel: a list of all named entities
+//!
+//! Each entity has buttons to toggle `EditorLocked` and `EditorHidden`.
+//! Entities without a `Name` are not shown, and neither are the editor's
+//! own entities (`EditorCleanup`, `EditorData`).
+
+use crate::crate_prelude::*;
+use crate::lock::{EditorHidden, EditorLocked};
+use crate::ui::panel::EditorPanelAppExt;
+
+/// Don't show more entities than this, to avoid spawning huge UIs
+const MAX_ROWS: usize = 256;
+
+pub(crate) struct OutlinerPlugin<S: States> {
+    pub state: S,
+}
+
+impl<S: States> Plugin for OutlinerPlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.add_editor_panel("Outliner", populate_outliner);
+        app.add_system(
+            update_outliner
+                .in_set(EditorSet)
+        );
+    }
+}
+
+/// The UI node containing the rows
+#[derive(Component)]
+struct OutlinerList {
+    /// Rebuild on the next update
+    dirty: bool,
+    /// The entities that have rows
+    shown: HashSet<Entity>,
+}
+
+#[derive(Clone, Copy)]
+enum OutlinerToggleKind {
+    Lock,
+    Hide,
+}
+
+/// Button for toggling a flag on an entity
+#[derive(Component)]
+struct OutlinerToggle {
+    target: Entity,
+    kind: OutlinerToggleKind,
+}
+
+fn populate_outliner(
+    In(e_contents): In<Entity>,
+    mut commands: Commands,
+) {
+    let list = commands.spawn((
+        NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Stretch,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        OutlinerList {
+            dirty: true,
+            shown: HashSet::default(),
+        },
+    )).id();
+    commands.entity(e_contents).push_children(&[list]);
+}
+
+fn update_outliner(
+    mut commands: Commands,
+    assets: Res<EditorAssets>,
+    q_changed: Query<
+        (),
+        (Or<(Changed<Name>, Added<EditorLocked>, Added<EditorHidden>)>, Without<EditorCleanup>, Without<EditorData>),
+    >,
+    mut removed_name: RemovedComponents<Name>,
+    mut removed_locked: RemovedComponents<EditorLocked>,
+    mut removed_hidden: RemovedComponents<EditorHidden>,
+    q_entities: Query<
+        (Entity, &Name, Option<&EditorLocked>, Option<&EditorHidden>),
+        (Without<EditorCleanup>, Without<EditorData>),
+    >,
+    mut q_list: Query<(Entity, &mut OutlinerList)>,
+) {
+    let changed = !q_changed.is_empty();
+    // removals only matter for entities that we show
+    let removed: Vec<Entity> = removed_name.iter()
+        .chain(removed_locked.iter())
+        .chain(removed_hidden.iter())
+        .collect();
+
+    for (e_list, mut list) in &mut q_list {
+        if !changed && !list.dirty && !removed.iter().any(|e| list.shown.contains(e)) {
+            continue;
+        }
+        list.dirty = false;
+        list.shown.clear();
+        commands.entity(e_list).despawn_descendants();
+
+        let mut entities: Vec<_> = q_entities.iter().collect();
+        entities.sort_by(|a, b| a.1.as_str().cmp(b.1.as_str()));
+        for (e, name, locked, hidden) in entities.into_iter().take(MAX_ROWS) {
+            list.shown.insert(e);
+            let row = commands.spawn((
+                NodeBundle {
+                    style: Style {
+                        align_items: AlignItems::Center,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            )).id();
+            let butt_lock = spawn_toggle(&mut commands, &*assets, e, OutlinerToggleKind::Lock, locked.is_some());
+            let butt_hide = spawn_toggle(&mut commands, &*assets, e, OutlinerToggleKind::Hide, hidden.is_some());
+            let label = commands.spawn((
+                TextBundle {
+                    style: Style {
+                        margin: UiRect::left(Val::Px(4.0)),
+                        ..Default::default()
+                    },
+                    text: Text::from_section(name.as_str(), TextStyle {
+                        font: assets.font.clone(),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+                ThemeText::default(),
+            )).id();
+            commands.entity(row).push_children(&[butt_lock, butt_hide, label]);
+            commands.entity(e_list).push_children(&[row]);
+        }
+    }
+}
+
+fn spawn_toggle(
+    commands: &mut Commands,
+    assets: &EditorAssets,
+    target: Entity,
+    kind: OutlinerToggleKind,
+    value: bool,
+) -> Entity {
+    let (on, off, tooltip) = match kind {
+        OutlinerToggleKind::Lock => ("[L]", "[ ]", crate::ui::tooltip::TooltipText {
+            title: "Lock".into(),
+            text: "Locked entities cannot be selected or moved.".into(),
+        }),
+        OutlinerToggleKind::Hide => ("[H]", "[ ]", crate::ui::tooltip::TooltipText {
+            title: "Hide".into(),
+            text: "Hidden entities are invisible and cannot be selected,\nuntil you exit the editor.".into(),
+        }),
+    };
+    let butt = commands.spawn((
+        ButtonBundle {
+            background_color: BackgroundColor(Color::NONE),
+            style: Style {
+                padding: UiRect::all(Val::Px(1.0)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        OutlinerToggle {
+            target, kind,
+        },
+        ClickBehavior::new().entity_system(outliner_toggle_handler),
+        tooltip,
+    )).id();
+    let text = commands.spawn((
+        TextBundle {
+            focus_policy: FocusPolicy::Pass,
+            text: Text::from_section(if value { on } else { off }, TextStyle {
+                font: assets.font.clone(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        ThemeText::default(),
+    )).id();
+    commands.entity(butt).push_children(&[text]);
+    butt
+}
+
+fn outliner_toggle_handler(
+    In(entity): In<Entity>,
+    mut commands: Commands,
+    q_butt: Query<&OutlinerToggle>,
+    q_flags: Query<(Option<&EditorLocked>, Option<&EditorHidden>)>,
+) {
+    let Ok(toggle) = q_butt.get(entity) else { return; };
+    let target = toggle.target;
+    let Ok((locked, hidden)) = q_flags.get(target) else { return; };
+    let (locked, hidden) = (locked.is_some(), hidden.is_some());
+    match toggle.kind {
+        OutlinerToggleKind::Lock => commands.add(move |world: &mut World| {
+            crate::lock::set_locked(world, target, !locked);
+        }),
+        OutlinerToggleKind::Hide => commands.add(move |world: &mut World| {
+            crate::lock::set_hidden(world, target, !hidden);
+        }),
+    }
+}
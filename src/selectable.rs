@@ -0,0 +1,339 @@
+//! Making things selectable with the mouse
+//!
+//! To make entities with your own component types selectable in the editor,
+//! implement `SelectableProvider` and add `SelectablePlugin::<YourProvider>`
+//! to your app. The provider tells the editor the local-space bounding
+//! rectangle of an entity, and optionally does more precise hit-testing.
+//! The editor does everything else: keeps a spatial index of all selectable
+//! entities up to date, tracks what the cursor is hovering over, and sends
+//! `SelectionCandidateEvent`s when that changes. The candidates under the
+//! cursor are ordered by Z for disambiguation, regardless of provider.
+//!
+//! This is how the editor's own support for sprites, texture atlas sprites,
+//! text and meshes is implemented.
+//!
+//! If an entity matches multiple providers, whichever updates it last wins.
+
+use std::any::TypeId;
+use std::marker::PhantomData;
+
+use bevy::ecs::query::{ReadOnlyWorldQuery, ROQueryItem};
+use bevy::ecs::system::{ReadOnlySystemParam, StaticSystemParam, SystemParamItem};
+
+use crate::crate_prelude::*;
+use crate::camera::WorldCursor;
+use crate::lock::Unpickable;
+use crate::selection::{Selected, Selection, SelectionCandidateEvent, SelectionCandidateSet, SelectionPending};
+use crate::spatial::{SpatialGrid, world_bounds};
+
+/// Cell size for the spatial index, in world units
+const INDEX_CELL_SIZE: f32 = 256.0;
+
+/// Implement this to make your own kinds of entities selectable
+///
+/// Add `SelectablePlugin::<YourProvider>` to your app to register it.
+///
+/// Entities with `EditorCleanup`, `EditorLocked` or `EditorHidden` are never selectable.
+pub trait SelectableProvider: Send + Sync + 'static {
+    /// The components of a selectable entity (what entities to consider)
+    type Query: ReadOnlyWorldQuery;
+    /// Filter to detect changes that affect the bounds (such as `Changed<Sprite>`)
+    ///
+    /// Changes to `GlobalTransform` are always detected.
+    type Changed: ReadOnlyWorldQuery;
+    /// Any other data needed (such as assets)
+    type Param: ReadOnlySystemParam;
+
+    /// Color of the selection highlight
+    const COLOR: Color = Color::PINK;
+
+    /// Compute the rectangle of an entity, in its local space
+    ///
+    /// Return `None` if it cannot be known yet (such as if some asset
+    /// is not loaded). It will be retried every frame.
+    fn bounds(
+        item: ROQueryItem<'_, Self::Query>,
+        param: &SystemParamItem<Self::Param>,
+    ) -> Option<Rect>;
+
+    /// Precisely check if the given point (in local space) is on the entity
+    ///
+    /// This is only called if the point is within `bounds`.
+    /// The default implementation always returns `true`.
+    fn hit_test(
+        item: ROQueryItem<'_, Self::Query>,
+        param: &SystemParamItem<Self::Param>,
+        bounds: Rect,
+        local: Vec2,
+    ) -> bool {
+        let _ = (item, param, bounds, local);
+        true
+    }
+}
+
+/// Systems that update the `SelectableIndex`
+#[derive(SystemSet, Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub(crate) struct SelectableIndexSet;
+
+/// Systems that check what is under the cursor
+#[derive(SystemSet, Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub(crate) struct SelectablePickSet;
+
+/// The common infrastructure for all `SelectableProvider`s (added by the editor)
+pub(crate) struct SelectablePickingPlugin<S: States> {
+    pub state: S,
+}
+
+impl<S: States> Plugin for SelectablePickingPlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SelectableIndex>();
+        app.init_resource::<SelectableHover>();
+        app.configure_set(SelectablePickSet.after(SelectableIndexSet));
+        app.add_system(
+            reset_hover
+                .in_schedule(OnEnter(Tool::SelectEntities))
+        );
+        // not only in the editor: removals are only reported for one frame,
+        // and things get despawned while playing too
+        app.add_system(
+            remove_despawned
+                .in_set(SelectableIndexSet)
+        );
+        app.add_system(
+            clear_invalidated
+                .in_set(EditorSet)
+                .after(SelectableIndexSet)
+        );
+        app.add_system(
+            emit_candidate_events
+                .in_set(EditorSet)
+                .after(SelectablePickSet)
+                .before(SelectionCandidateSet)
+                .run_if(with_tools(Tool::SelectEntities))
+                .run_if(crate::ui::cursor_not_over_ui)
+        );
+    }
+}
+
+/// Add this to your app to make things selectable using your `SelectableProvider`
+pub struct SelectablePlugin<P: SelectableProvider> {
+    _pd: PhantomData<P>,
+}
+
+impl<P: SelectableProvider> Default for SelectablePlugin<P> {
+    fn default() -> Self {
+        SelectablePlugin {
+            _pd: PhantomData,
+        }
+    }
+}
+
+impl<P: SelectableProvider> Plugin for SelectablePlugin<P> {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            update_index::<P>
+                .in_set(EditorSet)
+                .in_set(SelectableIndexSet)
+        );
+        app.add_system(
+            pick::<P>
+                .in_set(EditorSet)
+                .in_set(SelectablePickSet)
+                .after(crate::camera::WorldCursorSet)
+                .run_if(with_tools(Tool::SelectEntities))
+                .run_if(crate::ui::cursor_not_over_ui)
+        );
+    }
+}
+
+struct IndexEntry {
+    provider: TypeId,
+    color: Color,
+    /// in local space
+    rect: Rect,
+    /// To transform world coordinates into the entity's local space
+    inverse: Mat4,
+}
+
+/// Spatial index of all selectable entities, kept up to date incrementally
+#[derive(Resource)]
+pub struct SelectableIndex {
+    grid: SpatialGrid,
+    entries: HashMap<Entity, IndexEntry>,
+    /// Entities whose bounds changed since we last checked for hovering
+    changed: HashSet<Entity>,
+    /// Entities that must be recomputed, even if nothing changed on them
+    invalidated: HashSet<Entity>,
+}
+
+impl Default for SelectableIndex {
+    fn default() -> Self {
+        SelectableIndex {
+            grid: SpatialGrid::new(INDEX_CELL_SIZE),
+            entries: default(),
+            changed: default(),
+            invalidated: default(),
+        }
+    }
+}
+
+impl SelectableIndex {
+    /// Force the bounds of an entity to be recomputed
+    ///
+    /// Use this if something that your `SelectableProvider::bounds` depends on
+    /// changed, but that is not detected by `SelectableProvider::Changed`.
+    pub fn invalidate(&mut self, e: Entity) {
+        self.invalidated.insert(e);
+    }
+
+    fn insert(&mut self, e: Entity, provider: TypeId, color: Color, rect: Rect, xf: &GlobalTransform) {
+        self.grid.insert(e, world_bounds(rect, xf));
+        self.entries.insert(e, IndexEntry {
+            provider,
+            color,
+            rect,
+            inverse: xf.compute_matrix().inverse(),
+        });
+        self.changed.insert(e);
+    }
+
+    fn remove(&mut self, e: Entity) {
+        self.grid.remove(e);
+        self.entries.remove(&e);
+    }
+}
+
+/// What the cursor is over
+#[derive(Resource, Default)]
+struct SelectableHover {
+    /// Entities we have sent `Insert` events for
+    hovered: HashSet<Entity>,
+    /// Entities found by the providers this frame
+    now: HashSet<Entity>,
+}
+
+fn reset_hover(
+    mut hover: ResMut<SelectableHover>,
+) {
+    // the selection candidates are reset when entering the tool
+    hover.hovered.clear();
+    hover.now.clear();
+}
+
+fn update_index<P: SelectableProvider>(
+    mut index: ResMut<SelectableIndex>,
+    mut pending: Local<HashSet<Entity>>,
+    param: StaticSystemParam<P::Param>,
+    q_changed: Query<
+        (Entity, P::Query, &GlobalTransform),
+        (Or<(Changed<GlobalTransform>, P::Changed)>, Without<EditorCleanup>),
+    >,
+    q_all: Query<(Entity, P::Query, &GlobalTransform), Without<EditorCleanup>>,
+) {
+    let provider = TypeId::of::<P>();
+    let index = &mut *index;
+
+    // forget about entities that are gone or no longer match
+    pending.retain(|e| q_all.contains(*e));
+    let retry: Vec<Entity> = pending.iter().chain(index.invalidated.iter()).copied().collect();
+
+    let mut update = |index: &mut SelectableIndex, e: Entity, rect: Option<Rect>, xf: &GlobalTransform| {
+        if let Some(rect) = rect {
+            pending.remove(&e);
+            index.insert(e, provider, P::COLOR, rect, xf);
+        } else {
+            pending.insert(e);
+            index.remove(e);
+        }
+    };
+
+    for (e, item, xf) in &q_changed {
+        update(index, e, P::bounds(item, &*param), xf);
+    }
+
+    for (e, item, xf) in q_all.iter_many(retry) {
+        update(index, e, P::bounds(item, &*param), xf);
+    }
+}
+
+fn remove_despawned(
+    mut index: ResMut<SelectableIndex>,
+    mut removed: RemovedComponents<GlobalTransform>,
+) {
+    for e in removed.iter() {
+        index.remove(e);
+    }
+}
+
+fn clear_invalidated(
+    mut index: ResMut<SelectableIndex>,
+) {
+    if !index.invalidated.is_empty() {
+        index.invalidated.clear();
+    }
+}
+
+fn pick<P: SelectableProvider>(
+    crs: Res<WorldCursor>,
+    mut index: ResMut<SelectableIndex>,
+    mut hover: ResMut<SelectableHover>,
+    param: StaticSystemParam<P::Param>,
+    q: Query<P::Query, Without<EditorCleanup>>,
+    q_unpickable: Query<(), Unpickable>,
+) {
+    let provider = TypeId::of::<P>();
+    let mut stale = vec![];
+    for e in index.grid.query_point(crs.pos) {
+        let Some(entry) = index.entries.get(&e) else {
+            continue;
+        };
+        if entry.provider != provider || q_unpickable.contains(e) {
+            continue;
+        }
+        let Ok(item) = q.get(e) else {
+            // no longer has the components for this provider
+            stale.push(e);
+            continue;
+        };
+        let crs_local = (entry.inverse * crs.pos.extend(0.0).extend(1.0)).truncate().truncate();
+        if entry.rect.contains(crs_local) && P::hit_test(item, &*param, entry.rect, crs_local) {
+            hover.now.insert(e);
+        }
+    }
+    for e in stale {
+        index.remove(e);
+    }
+}
+
+fn emit_candidate_events(
+    mut index: ResMut<SelectableIndex>,
+    mut hover: ResMut<SelectableHover>,
+    q_excluded: Query<(), Or<(With<Selected>, With<Selection>, With<SelectionPending>)>>,
+    mut evw_candidate: EventWriter<SelectionCandidateEvent>,
+) {
+    let hover = &mut *hover;
+    let now_hovered: HashSet<Entity> = hover.now.drain()
+        .filter(|e| !q_excluded.contains(*e))
+        .collect();
+
+    // only send events when something actually changed
+    for e in hover.hovered.difference(&now_hovered) {
+        evw_candidate.send(SelectionCandidateEvent::Remove {
+            entity: *e,
+        });
+    }
+    for e in now_hovered.iter() {
+        if !hover.hovered.contains(e) || index.changed.contains(e) {
+            let Some(entry) = index.entries.get(e) else {
+                continue;
+            };
+            evw_candidate.send(SelectionCandidateEvent::Insert {
+                entity: *e,
+                color: entry.color,
+                bounds: entry.rect,
+            });
+        }
+    }
+    hover.hovered = now_hovered;
+    index.changed.clear();
+}
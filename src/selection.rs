@@ -59,6 +59,7 @@ impl<S: States> Plugin for SelectionPlugin<S> {
                 .in_set(EditorSet)
                 .run_if(with_tools(Tool::SelectEntities))
                 .run_if(input_just_pressed(MouseButton::Left))
+                .run_if(crate::ui::cursor_not_over_ui)
         );
         app.add_system(
             handle_candidate_events
@@ -83,6 +84,7 @@ impl<S: States> Plugin for SelectionPlugin<S> {
                 .before(EditorFlush)
                 .run_if(with_tools(Tool::SelectEntities))
                 .run_if(input_just_pressed(MouseButton::Left))
+                .run_if(crate::ui::cursor_not_over_ui)
         );
         app.add_system(
             update_pending_visual
@@ -109,6 +111,9 @@ impl<S: States> Plugin for SelectionPlugin<S> {
 /// of managing the selection entities and components when that happens.
 ///
 /// Note: other modules are responsible for keeping SelectionVisualBounds up to date.
+///
+/// Usually, it is easier to implement a `SelectableProvider`, which takes care
+/// of sending these events for you.
 pub enum SelectionCandidateEvent {
     Insert {
         entity: Entity,
@@ -121,8 +126,8 @@ pub enum SelectionCandidateEvent {
 }
 
 #[derive(Resource, Default)]
-struct SelectionCandidates {
-    candidates: HashMap<Entity, (Color, Rect)>,
+pub(crate) struct SelectionCandidates {
+    pub(crate) candidates: HashMap<Entity, (Color, Rect)>,
 }
 
 /// Used on the highlight to show the current selection candidate
@@ -160,7 +165,7 @@ impl Default for SelectionVisualBounds {
 }
 
 #[derive(Component)]
-pub(crate) struct SelectionVisualColor(Color);
+pub(crate) struct SelectionVisualColor(pub Color);
 
 impl Default for SelectionVisualColor {
     fn default() -> Self {
@@ -203,6 +208,30 @@ impl SelectionBundle {
     }
 }
 
+/// Make an entity selected, as if the user had clicked on it
+pub(crate) fn select_entity(world: &mut World, target: Entity, bounds: Rect, color: Color) {
+    let Some(xf) = world.get::<GlobalTransform>(target).copied() else {
+        return;
+    };
+    let e = world.spawn(
+        SelectionBundle::from_entity(target)
+            .with_bounds(bounds)
+            .with_color(color)
+            .with_transform(xf.compute_transform())
+    ).id();
+    world.entity_mut(target).insert(Selected { selection: e });
+}
+
+/// Make an entity no longer selected
+pub(crate) fn deselect_entity(world: &mut World, target: Entity) {
+    let Some(selected) = world.get_entity_mut(target).and_then(|mut e| e.take::<Selected>()) else {
+        return;
+    };
+    if let Some(e) = world.get_entity_mut(selected.selection) {
+        e.despawn_recursive();
+    }
+}
+
 fn setup_selection_pending(
     mut commands: Commands,
 ) {
@@ -341,7 +370,12 @@ fn confirm_selection_on_click(
                 .with_color(color.0.with_a(0.5))
                 .with_transform(xf.compute_transform())
         ).id();
-        commands.entity(target).insert(Selected { selection: e });
+        // the target might have been despawned since it became a candidate
+        if let Some(mut ec) = commands.get_entity(target) {
+            ec.insert(Selected { selection: e });
+        } else {
+            commands.entity(e).despawn();
+        }
         evw_candidate.send(SelectionCandidateEvent::Remove { entity: target });
     }
     pending.target = None;
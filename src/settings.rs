@@ -0,0 +1,285 @@
+//! Persisting the editor's settings to disk
+//!
+//! The panel layout (positions, collapsed/minified/closed state, docking)
+//! and preferences (active tool, tool hotkeys, grid, snapping, camera
+//! controls) are saved into a RON file in the directory given by
+//! `EditorSettingsDir`.
+//!
+//! They are loaded and restored when entering the editor, and saved
+//! periodically (if anything changed) while in the editor and when exiting.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::crate_prelude::*;
+use crate::camera::CameraControls;
+use crate::grid::EditorGrid;
+use crate::transform::SnapSettings;
+
+const SETTINGS_FILE: &str = "settings.ron";
+
+/// How often to check if the settings need to be saved
+const SAVE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Where the editor saves its settings
+///
+/// Insert this resource to use a different directory, or set it to `None`
+/// to not save anything to disk. Then, the settings are only kept in memory
+/// (while the app is running).
+#[derive(Resource, Debug, Clone)]
+pub struct EditorSettingsDir(pub Option<PathBuf>);
+
+impl Default for EditorSettingsDir {
+    fn default() -> Self {
+        EditorSettingsDir(Some(PathBuf::from(".iyes2d_editor")))
+    }
+}
+
+impl EditorSettingsDir {
+    fn file(&self) -> Option<PathBuf> {
+        self.0.as_ref().map(|dir| dir.join(SETTINGS_FILE))
+    }
+}
+
+/// Systems that restore state from the `EditorSettings`, after entering the editor
+#[derive(SystemSet, Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub(crate) struct SettingsRestoreSet;
+
+/// Systems that update the `EditorSettings` from the current state
+#[derive(SystemSet, Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub(crate) struct SettingsCollectSet;
+
+pub(crate) struct SettingsPlugin<S: States> {
+    pub state: S,
+}
+
+impl<S: States> Plugin for SettingsPlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditorSettingsDir>();
+        app.init_resource::<EditorSettings>();
+        app.configure_set(
+            SettingsRestoreSet
+                .in_set(EditorSet)
+                .run_if(resource_exists::<PendingSettingsRestore>())
+        );
+        app.configure_set(
+            SettingsCollectSet
+                .in_set(EditorSet)
+                .after(SettingsRestoreSet)
+                .run_if(not_restoring_settings)
+        );
+        app.add_systems(
+            (
+                load_settings,
+            ).in_schedule(OnEnter(self.state.clone()))
+        );
+        app.add_systems(
+            (
+                save_settings_on_exit,
+            ).in_schedule(OnExit(self.state.clone()))
+        );
+        app.add_system(
+            finish_restore
+                .in_set(EditorSet)
+                .after(SettingsRestoreSet)
+                .run_if(resource_exists::<PendingSettingsRestore>())
+        );
+        app.add_system(
+            collect_preferences
+                .in_set(SettingsCollectSet)
+        );
+        app.add_system(
+            save_settings
+                .in_set(EditorSet)
+                .after(SettingsCollectSet)
+        );
+    }
+}
+
+/// Everything saved in the settings file
+///
+/// The preferences are `None` until first saved, so that we don't
+/// override what the app configured.
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct EditorSettings {
+    pub tool: Option<Tool>,
+    pub hotkeys: Option<ToolHotkeys>,
+    pub grid: Option<EditorGrid>,
+    pub snap: Option<SnapSettings>,
+    pub camera: Option<CameraControls>,
+    /// By panel title
+    pub panels: BTreeMap<String, PanelSettings>,
+    pub dock: DockSettings,
+}
+
+/// Saved state of a panel
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct PanelSettings {
+    /// Position of the top left corner, when not docked
+    pub position: Option<Vec2>,
+    /// Size, if it was resized
+    pub size: Option<Vec2>,
+    /// Collapsed into the titlebar
+    pub collapsed: bool,
+    /// Collapsed into a button in the minibar
+    pub minified: bool,
+    pub closed: bool,
+}
+
+/// Saved docking layout
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct DockSettings {
+    /// Left, Right, Top, Bottom
+    pub areas: Vec<DockAreaSettings>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct DockAreaSettings {
+    pub size: f32,
+    pub groups: Vec<DockGroupSettings>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct DockGroupSettings {
+    /// Panel titles
+    pub panels: Vec<String>,
+    pub active: usize,
+    pub weight: f32,
+}
+
+/// Present until the state has been restored after entering the editor
+#[derive(Resource)]
+pub(crate) struct PendingSettingsRestore;
+
+fn not_restoring_settings(pending: Option<Res<PendingSettingsRestore>>) -> bool {
+    pending.is_none()
+}
+
+fn load_settings(
+    mut commands: Commands,
+    dir: Res<EditorSettingsDir>,
+    mut settings: ResMut<EditorSettings>,
+    mut grid: ResMut<EditorGrid>,
+    mut snap: ResMut<SnapSettings>,
+    mut camera: ResMut<CameraControls>,
+    mut hotkeys: ResMut<ToolHotkeys>,
+    mut next_tool: ResMut<NextState<Tool>>,
+) {
+    // if there is no file, keep whatever we had in memory
+    if let Some(path) = dir.file() {
+        match std::fs::read_to_string(&path) {
+            Ok(ron) => match ron::from_str(&ron) {
+                Ok(loaded) => *settings = loaded,
+                Err(e) => error!("Could not parse editor settings from {:?}: {}", path, e),
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => error!("Could not read editor settings from {:?}: {}", path, e),
+        }
+    }
+    if let Some(tool) = settings.tool {
+        next_tool.set(tool);
+    }
+    if let Some(saved) = &settings.grid {
+        *grid = saved.clone();
+    }
+    if let Some(saved) = &settings.snap {
+        *snap = saved.clone();
+    }
+    if let Some(saved) = &settings.camera {
+        *camera = saved.clone();
+    }
+    if let Some(saved) = &settings.hotkeys {
+        *hotkeys = saved.clone();
+    }
+    // restore the panels on the next frame, once they exist
+    commands.insert_resource(PendingSettingsRestore);
+}
+
+fn finish_restore(
+    mut commands: Commands,
+) {
+    commands.remove_resource::<PendingSettingsRestore>();
+}
+
+fn collect_preferences(
+    mut settings: ResMut<EditorSettings>,
+    tool: Res<State<Tool>>,
+    grid: Res<EditorGrid>,
+    snap: Res<SnapSettings>,
+    camera: Res<CameraControls>,
+    hotkeys: Res<ToolHotkeys>,
+) {
+    if settings.tool != Some(tool.0) {
+        settings.tool = Some(tool.0);
+    }
+    if grid.is_changed() || settings.grid.is_none() {
+        settings.grid = Some(grid.clone());
+    }
+    if snap.is_changed() || settings.snap.is_none() {
+        settings.snap = Some(snap.clone());
+    }
+    if camera.is_changed() || settings.camera.is_none() {
+        settings.camera = Some(camera.clone());
+    }
+    if hotkeys.is_changed() || settings.hotkeys.is_none() {
+        settings.hotkeys = Some(hotkeys.clone());
+    }
+}
+
+/// Save to disk, if anything changed since last time
+fn save_settings(
+    time: Res<Time>,
+    dir: Res<EditorSettingsDir>,
+    settings: Res<EditorSettings>,
+    mut timer: Local<Option<Timer>>,
+    mut dirty: Local<bool>,
+    mut last: Local<String>,
+) {
+    // remember changes from any frame, not just when the timer finishes
+    if settings.is_changed() {
+        *dirty = true;
+    }
+    let timer = timer.get_or_insert_with(|| Timer::new(SAVE_INTERVAL, TimerMode::Repeating));
+    timer.tick(time.delta());
+    if !timer.just_finished() || !*dirty {
+        return;
+    }
+    *dirty = false;
+    write_settings(&dir, &settings, &mut last);
+}
+
+fn save_settings_on_exit(
+    dir: Res<EditorSettingsDir>,
+    settings: Res<EditorSettings>,
+) {
+    write_settings(&dir, &settings, &mut String::new());
+}
+
+fn write_settings(dir: &EditorSettingsDir, settings: &EditorSettings, last: &mut String) {
+    let (Some(dir_path), Some(path)) = (&dir.0, dir.file()) else {
+        return;
+    };
+    let ron = match ron::ser::to_string_pretty(settings, Default::default()) {
+        Ok(ron) => ron,
+        Err(e) => {
+            error!("Could not serialize editor settings: {}", e);
+            return;
+        }
+    };
+    if ron == *last {
+        return;
+    }
+    if let Err(e) = std::fs::create_dir_all(dir_path).and_then(|_| std::fs::write(&path, &ron)) {
+        error!("Could not save editor settings to {:?}: {}", path, e);
+        return;
+    }
+    debug!("Saved editor settings to {:?}", path);
+    *last = ron;
+}
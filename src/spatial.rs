@@ -0,0 +1,101 @@
+//! Spatial acceleration structure for picking
+//!
+//! A simple uniform grid ("spatial hash"): the world is divided into square
+//! cells, and each entity is registered in all the cells its world-space
+//! bounding box overlaps. Point queries only need to look at one cell.
+
+use crate::crate_prelude::*;
+
+/// Entities spanning more cells than this (in either dimension) are not
+/// put into cells, but into a separate list that is always checked
+const MAX_CELLS_SPAN: i32 = 32;
+
+pub(crate) struct SpatialGrid {
+    cell_size: f32,
+    cells: HashMap<IVec2, HashSet<Entity>>,
+    /// Entities too big to be put in cells
+    large: HashSet<Entity>,
+    /// Where each entity is currently registered (min/max cell)
+    entries: HashMap<Entity, (IVec2, IVec2)>,
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f32) -> Self {
+        SpatialGrid {
+            cell_size,
+            cells: default(),
+            large: default(),
+            entries: default(),
+        }
+    }
+
+    fn cell_of(&self, p: Vec2) -> IVec2 {
+        (p / self.cell_size).floor().as_ivec2()
+    }
+
+    /// Add or update an entity, given its world-space bounding box
+    pub fn insert(&mut self, entity: Entity, aabb: Rect) {
+        let min = self.cell_of(aabb.min);
+        let max = self.cell_of(aabb.max);
+        if self.entries.get(&entity) == Some(&(min, max)) {
+            return;
+        }
+        self.remove(entity);
+        if max.x - min.x > MAX_CELLS_SPAN || max.y - min.y > MAX_CELLS_SPAN {
+            self.large.insert(entity);
+        } else {
+            for y in min.y..=max.y {
+                for x in min.x..=max.x {
+                    self.cells.entry(IVec2::new(x, y)).or_default().insert(entity);
+                }
+            }
+        }
+        self.entries.insert(entity, (min, max));
+    }
+
+    pub fn remove(&mut self, entity: Entity) {
+        let Some((min, max)) = self.entries.remove(&entity) else {
+            return;
+        };
+        if !self.large.remove(&entity) {
+            for y in min.y..=max.y {
+                for x in min.x..=max.x {
+                    let cell = IVec2::new(x, y);
+                    if let Some(set) = self.cells.get_mut(&cell) {
+                        set.remove(&entity);
+                        if set.is_empty() {
+                            self.cells.remove(&cell);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// All entities whose bounding box might contain the given point
+    pub fn query_point(&self, p: Vec2) -> impl Iterator<Item = Entity> + '_ {
+        self.cells.get(&self.cell_of(p))
+            .into_iter()
+            .flat_map(|set| set.iter())
+            .chain(self.large.iter())
+            .copied()
+    }
+}
+
+/// Compute the world-space axis-aligned bounding box of a local-space rectangle
+pub(crate) fn world_bounds(rect: Rect, xf: &GlobalTransform) -> Rect {
+    let corners = [
+        rect.min,
+        Vec2::new(rect.max.x, rect.min.y),
+        Vec2::new(rect.min.x, rect.max.y),
+        rect.max,
+    ];
+    let mut min = Vec2::splat(f32::MAX);
+    let mut max = Vec2::splat(f32::MIN);
+    for corner in corners {
+        let p = xf.transform_point(corner.extend(0.0)).truncate();
+        min = min.min(p);
+        max = max.max(p);
+    }
+    Rect { min, max }
+}
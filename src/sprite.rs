@@ -1,7 +1,11 @@
+use bevy::ecs::query::ROQueryItem;
+use bevy::ecs::system::SystemParamItem;
+use bevy::render::render_resource::TextureFormat;
+use bevy::sprite::Anchor;
+
 use crate::crate_prelude::*;
 
-use crate::camera::WorldCursor;
-use crate::selection::{Selected, Selection, SelectionCandidateEvent, SelectionCandidateSet, SelectionPending};
+use crate::selectable::{SelectablePlugin, SelectableProvider};
 
 pub(crate) struct SpriteEditorPlugin<S: States> {
     pub state: S,
@@ -9,55 +13,210 @@ pub(crate) struct SpriteEditorPlugin<S: States> {
 
 impl<S: States> Plugin for SpriteEditorPlugin<S> {
     fn build(&self, app: &mut App) {
-        app.add_system(
-            select_sprites
-                .in_set(EditorSet)
-                .before(SelectionCandidateSet)
-                .run_if(with_tools(Tool::SelectEntities))
-        );
-    }
-}
-
-fn select_sprites(
-    crs: Res<WorldCursor>,
-    q_sprite: Query<(Entity, &Sprite, &Handle<Image>, &GlobalTransform), (Without<Selected>, Without<Selection>, Without<SelectionPending>)>,
-    images: Res<Assets<Image>>,
-    mut evw_candidate: EventWriter<SelectionCandidateEvent>,
-) {
-    // PERF: find a way to reduce the number of checks because this will scale horribly
-
-    for (e, sprite, handle, xf) in &q_sprite {
-        let crs_local = xf.compute_matrix().inverse() * crs.pos.extend(0.0).extend(1.0);
-
-        // do the same arithmetic that bevy does when calculating the vertices of the sprite quad
-        let sprite_dimensions = if let Some(custom_size) = sprite.custom_size {
-            custom_size
-        } else if let Some(image) = images.get(handle) {
-            image.size()
-        } else {
-            continue;
+        app.init_resource::<SpritePickingSettings>();
+        app.register_type::<AlphaPicking>();
+        app.add_plugin(SelectablePlugin::<SpriteProvider>::default());
+        app.add_plugin(SelectablePlugin::<TextureAtlasSpriteProvider>::default());
+    }
+}
+
+/// Global settings for how sprites are picked (selected with the mouse)
+#[derive(Resource, Debug, Clone)]
+pub struct SpritePickingSettings {
+    /// Pick sprites only where their image is not transparent, for all sprites
+    ///
+    /// If this is disabled, you can still enable it for specific entities
+    /// using the `AlphaPicking` component.
+    pub alpha_picking: bool,
+    /// Texels with alpha above this value are considered opaque
+    pub alpha_threshold: f32,
+}
+
+impl Default for SpritePickingSettings {
+    fn default() -> Self {
+        SpritePickingSettings {
+            alpha_picking: false,
+            alpha_threshold: 0.5,
+        }
+    }
+}
+
+/// Pick this sprite only where its image is not transparent
+///
+/// Texels with alpha above `threshold` are considered opaque.
+/// This can be enabled for all sprites via `SpritePickingSettings`.
+#[derive(Component, Debug, Clone, Reflect, FromReflect)]
+#[reflect(Component)]
+pub struct AlphaPicking {
+    pub threshold: f32,
+}
+
+impl Default for AlphaPicking {
+    fn default() -> Self {
+        AlphaPicking {
+            threshold: 0.5,
+        }
+    }
+}
+
+/// Makes `Sprite` entities selectable
+pub struct SpriteProvider;
+
+impl SelectableProvider for SpriteProvider {
+    type Query = (&'static Sprite, &'static Handle<Image>, Option<&'static AlphaPicking>);
+    type Changed = Or<(Changed<Sprite>, Changed<Handle<Image>>)>;
+    type Param = (Res<'static, Assets<Image>>, Res<'static, SpritePickingSettings>);
+
+    fn bounds(
+        (sprite, handle, _): ROQueryItem<'_, Self::Query>,
+        (images, _): &SystemParamItem<Self::Param>,
+    ) -> Option<Rect> {
+        sprite_rect(sprite, handle, images)
+    }
+
+    fn hit_test(
+        (sprite, handle, alpha): ROQueryItem<'_, Self::Query>,
+        (images, settings): &SystemParamItem<Self::Param>,
+        bounds: Rect,
+        local: Vec2,
+    ) -> bool {
+        let Some(threshold) = alpha_threshold(alpha, settings) else {
+            return true;
+        };
+        let Some(image) = images.get(handle) else {
+            return true;
         };
-        let anchor = sprite.anchor.as_vec();
-        let rect = Rect::new(
-            (-0.5 - anchor.x) * sprite_dimensions.x,
-            (-0.5 - anchor.y) * sprite_dimensions.y,
-            ( 0.5 - anchor.x) * sprite_dimensions.x,
-            ( 0.5 - anchor.y) * sprite_dimensions.y,
-        );
-
-        // PERF: dont spam these events every frame
-        if crs_local.x >= rect.min.x && crs_local.y >= rect.min.y &&
-           crs_local.x <= rect.max.x && crs_local.y <= rect.max.y
-        {
-            evw_candidate.send(SelectionCandidateEvent::Insert {
-                entity: e,
-                color: Color::PINK,
-                bounds: rect,
-            });
-        } else {
-            evw_candidate.send(SelectionCandidateEvent::Remove {
-                entity: e,
-            });
+        let texel = texel_at(bounds, sprite.rect, sprite.flip_x, sprite.flip_y, local, image.size());
+        is_opaque(image, texel, threshold)
+    }
+}
+
+/// Makes `TextureAtlasSprite` entities selectable
+pub struct TextureAtlasSpriteProvider;
+
+impl SelectableProvider for TextureAtlasSpriteProvider {
+    type Query = (&'static TextureAtlasSprite, &'static Handle<TextureAtlas>, Option<&'static AlphaPicking>);
+    type Changed = Or<(Changed<TextureAtlasSprite>, Changed<Handle<TextureAtlas>>)>;
+    type Param = (
+        Res<'static, Assets<TextureAtlas>>,
+        Res<'static, Assets<Image>>,
+        Res<'static, SpritePickingSettings>,
+    );
+
+    fn bounds(
+        (sprite, handle, _): ROQueryItem<'_, Self::Query>,
+        (atlases, _, _): &SystemParamItem<Self::Param>,
+    ) -> Option<Rect> {
+        let atlas = atlases.get(handle)?;
+        let texture_rect = atlas.textures.get(sprite.index)?;
+        let sprite_dimensions = sprite.custom_size.unwrap_or(texture_rect.size());
+        Some(anchored_rect(sprite_dimensions, &sprite.anchor))
+    }
+
+    fn hit_test(
+        (sprite, handle, alpha): ROQueryItem<'_, Self::Query>,
+        (atlases, images, settings): &SystemParamItem<Self::Param>,
+        bounds: Rect,
+        local: Vec2,
+    ) -> bool {
+        let Some(threshold) = alpha_threshold(alpha, settings) else {
+            return true;
+        };
+        let Some(atlas) = atlases.get(handle) else {
+            return true;
+        };
+        let Some(image) = images.get(&atlas.texture) else {
+            return true;
+        };
+        let texture_rect = atlas.textures.get(sprite.index).copied();
+        let texel = texel_at(bounds, texture_rect, sprite.flip_x, sprite.flip_y, local, image.size());
+        is_opaque(image, texel, threshold)
+    }
+}
+
+/// The alpha threshold to use, if alpha picking is enabled for a sprite
+fn alpha_threshold(alpha: Option<&AlphaPicking>, settings: &SpritePickingSettings) -> Option<f32> {
+    if let Some(alpha) = alpha {
+        Some(alpha.threshold)
+    } else if settings.alpha_picking {
+        Some(settings.alpha_threshold)
+    } else {
+        None
+    }
+}
+
+/// Find the texel of the image at the given point (in the sprite's local space)
+fn texel_at(rect: Rect, texture_rect: Option<Rect>, flip_x: bool, flip_y: bool, local: Vec2, image_size: Vec2) -> UVec2 {
+    // the image's rows go from top to bottom
+    let mut uv = (local - rect.min) / rect.size();
+    uv.y = 1.0 - uv.y;
+    if flip_x {
+        uv.x = 1.0 - uv.x;
+    }
+    if flip_y {
+        uv.y = 1.0 - uv.y;
+    }
+    let texture_rect = texture_rect.unwrap_or(Rect {
+        min: Vec2::ZERO,
+        max: image_size,
+    });
+    let px = texture_rect.min + uv * texture_rect.size();
+    let max = image_size.as_uvec2();
+    px.floor().as_uvec2().min(UVec2::new(max.x.saturating_sub(1), max.y.saturating_sub(1)))
+}
+
+fn is_opaque(image: &Image, texel: UVec2, threshold: f32) -> bool {
+    // if we can't read the alpha, just treat it as opaque
+    texel_alpha(image, texel).map(|a| a > threshold).unwrap_or(true)
+}
+
+/// Read the alpha value of a texel of an image
+///
+/// Returns `None` if the texture format is not supported.
+fn texel_alpha(image: &Image, texel: UVec2) -> Option<f32> {
+    let width = image.texture_descriptor.size.width;
+    let i = (texel.y * width + texel.x) as usize;
+    match image.texture_descriptor.format {
+        TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb |
+        TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => {
+            image.data.get(i * 4 + 3).map(|a| *a as f32 / 255.0)
         }
+        TextureFormat::Rgba16Unorm => {
+            image.data.get(i * 8 + 6..i * 8 + 8)
+                .map(|a| u16::from_le_bytes([a[0], a[1]]) as f32 / 65535.0)
+        }
+        TextureFormat::Rgba32Float => {
+            image.data.get(i * 16 + 12..i * 16 + 16)
+                .map(|a| f32::from_le_bytes([a[0], a[1], a[2], a[3]]))
+        }
+        // formats without alpha are always opaque
+        TextureFormat::R8Unorm | TextureFormat::Rg8Unorm => Some(1.0),
+        _ => None,
     }
 }
+
+/// Compute the local-space rectangle of a sprite
+///
+/// Returns `None` if the image is not loaded yet.
+fn sprite_rect(sprite: &Sprite, handle: &Handle<Image>, images: &Assets<Image>) -> Option<Rect> {
+    // do the same arithmetic that bevy does when calculating the vertices of the sprite quad
+    let sprite_dimensions = if let Some(custom_size) = sprite.custom_size {
+        custom_size
+    } else if let Some(rect) = sprite.rect {
+        rect.size()
+    } else {
+        images.get(handle)?.size()
+    };
+    Some(anchored_rect(sprite_dimensions, &sprite.anchor))
+}
+
+/// The local-space rectangle of a sprite quad of the given size
+pub(crate) fn anchored_rect(size: Vec2, anchor: &Anchor) -> Rect {
+    let anchor = anchor.as_vec();
+    Rect::new(
+        (-0.5 - anchor.x) * size.x,
+        (-0.5 - anchor.y) * size.y,
+        ( 0.5 - anchor.x) * size.x,
+        ( 0.5 - anchor.y) * size.y,
+    )
+}
@@ -0,0 +1,364 @@
+//! Selecting and editing world-space text (`Text2dBundle`)
+//!
+//! Text entities are picked by the area of their laid-out text.
+//!
+//! Double-clicking on a text entity (with the selection tool) enters text
+//! edit mode, where you can type directly into its sections. Left/Right,
+//! Home/End move the caret, Tab/Shift+Tab switch between sections, and
+//! Escape (or clicking elsewhere) stops editing. While editing, the
+//! editor's other keyboard shortcuts are disabled.
+
+use bevy::ecs::query::ROQueryItem;
+use bevy::ecs::system::SystemParamItem;
+use bevy::sprite::Anchor;
+use bevy::text::{Text2dBounds, TextLayoutInfo};
+use bevy::window::PrimaryWindow;
+
+use crate::crate_prelude::*;
+use crate::camera::WorldCursor;
+use crate::lock::Unpickable;
+use crate::selectable::{SelectablePlugin, SelectableProvider};
+use crate::sprite::anchored_rect;
+
+/// Max time between clicks to count as a double-click
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(400);
+
+pub(crate) struct TextEditorPlugin<S: States> {
+    pub state: S,
+}
+
+impl<S: States> Plugin for TextEditorPlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TextEditState>();
+        app.add_plugin(SelectablePlugin::<Text2dProvider>::default());
+        app.add_systems(
+            (
+                setup_text_caret,
+            ).in_schedule(OnEnter(self.state.clone()))
+        );
+        app.add_systems(
+            (
+                stop_text_edit,
+            ).in_schedule(OnExit(self.state.clone()))
+        );
+        app.add_systems(
+            (
+                stop_text_edit,
+            ).in_schedule(OnExit(Tool::SelectEntities))
+        );
+        app.add_system(
+            text_edit_click
+                .in_set(EditorSet)
+                .after(crate::camera::WorldCursorSet)
+                .run_if(with_tools(Tool::SelectEntities))
+                .run_if(input_just_pressed(MouseButton::Left))
+                .run_if(crate::ui::cursor_not_over_ui)
+        );
+        app.add_system(
+            text_edit_input
+                .in_set(EditorSet)
+                .after(text_edit_click)
+        );
+        app.add_system(
+            update_text_caret
+                .in_set(EditorSet)
+                .after(text_edit_input)
+        );
+    }
+}
+
+/// State of the in-world text editing
+#[derive(Resource, Default)]
+pub struct TextEditState {
+    entity: Option<Entity>,
+    /// Index of the section we are typing into
+    section: usize,
+    /// Byte offset into the section's string
+    cursor: usize,
+}
+
+impl TextEditState {
+    /// The text entity currently being edited, if any
+    pub fn entity(&self) -> Option<Entity> {
+        self.entity
+    }
+}
+
+/// Run condition: are we typing into a text entity?
+pub fn is_editing_text(state: Res<TextEditState>) -> bool {
+    state.entity.is_some()
+}
+
+/// Run condition for keyboard shortcuts, which should not trigger while typing
+///
+/// This also accounts for typing into editor UI (such as renaming a layer).
+pub fn not_editing_text(state: Res<TextEditState>, ui_input: Res<crate::ui::UiTextInputActive>) -> bool {
+    state.entity.is_none() && !ui_input.is_active()
+}
+
+/// Marker for the sprite showing the text edit caret
+#[derive(Component)]
+struct TextEditCaret;
+
+/// Compute the local-space rectangle of a text entity
+pub(crate) fn text2d_rect(
+    text: &Text,
+    layout: &TextLayoutInfo,
+    bounds: &Text2dBounds,
+    anchor: &Anchor,
+    scale_factor: f32,
+) -> Rect {
+    // the layout is in physical pixels
+    let mut size = layout.size / scale_factor;
+    if size.x <= 0.0 || size.y <= 0.0 {
+        // empty text; still make it possible to pick it
+        let font_size = text.sections.first().map(|s| s.style.font_size).unwrap_or(16.0);
+        size = Vec2::new(
+            if bounds.size.x.is_finite() { bounds.size.x } else { font_size / 2.0 },
+            font_size,
+        );
+    }
+    anchored_rect(size, anchor)
+}
+
+/// Makes `Text2dBundle` entities selectable
+pub struct Text2dProvider;
+
+impl SelectableProvider for Text2dProvider {
+    type Query = (&'static Text, &'static TextLayoutInfo, &'static Text2dBounds, &'static Anchor);
+    type Changed = Or<(Changed<TextLayoutInfo>, Changed<Text2dBounds>, Changed<Anchor>)>;
+    type Param = Query<'static, 'static, &'static Window, With<PrimaryWindow>>;
+
+    fn bounds(
+        (text, layout, bounds, anchor): ROQueryItem<'_, Self::Query>,
+        q_window: &SystemParamItem<Self::Param>,
+    ) -> Option<Rect> {
+        Some(text2d_rect(text, layout, bounds, anchor, scale_factor(q_window)))
+    }
+}
+
+fn scale_factor(q_window: &Query<&Window, With<PrimaryWindow>>) -> f32 {
+    q_window.get_single().map(|w| w.scale_factor() as f32).unwrap_or(1.0)
+}
+
+fn stop_text_edit(
+    mut state: ResMut<TextEditState>,
+) {
+    state.entity = None;
+}
+
+fn text_edit_click(
+    time: Res<Time>,
+    crs: Res<WorldCursor>,
+    mut state: ResMut<TextEditState>,
+    mut last_click: Local<Option<(Entity, Duration)>>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_text: Query<(Entity, &Text, &TextLayoutInfo, &Text2dBounds, &Anchor, &GlobalTransform)>,
+    q_unpickable: Query<(), Unpickable>,
+) {
+    // NOTE: assumes .run_if(input_just_pressed(MouseButton::Left))
+    let scale = scale_factor(&q_window);
+    let mut hit: Option<(Entity, f32)> = None;
+    for (e, text, layout, bounds, anchor, xf) in &q_text {
+        if q_unpickable.contains(e) {
+            continue;
+        }
+        let rect = text2d_rect(text, layout, bounds, anchor, scale);
+        let crs_local = xf.compute_matrix().inverse() * crs.pos.extend(0.0).extend(1.0);
+        if rect.contains(crs_local.truncate().truncate()) {
+            let z = xf.translation().z;
+            if hit.map(|(_, hit_z)| z > hit_z).unwrap_or(true) {
+                hit = Some((e, z));
+            }
+        }
+    }
+    let hit = hit.map(|(e, _)| e);
+
+    if state.entity.is_some() && state.entity != hit {
+        state.entity = None;
+    }
+
+    let now = time.elapsed();
+    match (hit, *last_click) {
+        (Some(e), Some((last_e, last_time))) if e == last_e && now - last_time < DOUBLE_CLICK_TIME => {
+            // start typing at the end of the text
+            let Ok((_, text, ..)) = q_text.get(e) else {
+                return;
+            };
+            state.entity = Some(e);
+            state.section = text.sections.len().saturating_sub(1);
+            state.cursor = text.sections.last().map(|s| s.value.len()).unwrap_or(0);
+            *last_click = None;
+        }
+        (Some(e), _) => {
+            *last_click = Some((e, now));
+        }
+        (None, _) => {
+            *last_click = None;
+        }
+    }
+}
+
+fn text_edit_input(
+    kbd: Res<Input<KeyCode>>,
+    mut evr_char: EventReader<ReceivedCharacter>,
+    mut state: ResMut<TextEditState>,
+    mut q_text: Query<&mut Text>,
+) {
+    // TODO: transition to a proper input mgmt framework like LWIM
+    // read the characters every frame, so that anything typed before
+    // editing started is thrown away, rather than inserted into the text
+    let chars: Vec<char> = evr_char.iter().map(|ev| ev.char).collect();
+    if state.entity.is_none() {
+        return;
+    }
+    let Some(mut text) = state.entity.and_then(|e| q_text.get_mut(e).ok()) else {
+        state.entity = None;
+        return;
+    };
+    if kbd.just_pressed(KeyCode::Escape) || text.sections.is_empty() {
+        state.entity = None;
+        return;
+    }
+
+    // the text might have been changed by something else
+    state.section = state.section.min(text.sections.len() - 1);
+    let value = &text.sections[state.section].value;
+    state.cursor = state.cursor.min(value.len());
+    while !value.is_char_boundary(state.cursor) {
+        state.cursor -= 1;
+    }
+
+    let prev_boundary = |s: &str, i: usize| s[..i].char_indices().next_back().map(|(i, _)| i);
+    let next_boundary = |s: &str, i: usize| s[i..].chars().next().map(|c| i + c.len_utf8());
+
+    let n_sections = text.sections.len();
+    if kbd.just_pressed(KeyCode::Tab) {
+        state.section = if kbd.any_pressed([KeyCode::LShift, KeyCode::RShift]) {
+            (state.section + n_sections - 1) % n_sections
+        } else {
+            (state.section + 1) % n_sections
+        };
+        state.cursor = text.sections[state.section].value.len();
+    }
+    if kbd.just_pressed(KeyCode::Left) {
+        match prev_boundary(&text.sections[state.section].value, state.cursor) {
+            Some(i) => state.cursor = i,
+            None if state.section > 0 => {
+                state.section -= 1;
+                state.cursor = text.sections[state.section].value.len();
+            }
+            None => {}
+        }
+    }
+    if kbd.just_pressed(KeyCode::Right) {
+        match next_boundary(&text.sections[state.section].value, state.cursor) {
+            Some(i) => state.cursor = i,
+            None if state.section + 1 < n_sections => {
+                state.section += 1;
+                state.cursor = 0;
+            }
+            None => {}
+        }
+    }
+    if kbd.just_pressed(KeyCode::Home) {
+        state.cursor = 0;
+    }
+    if kbd.just_pressed(KeyCode::End) {
+        state.cursor = text.sections[state.section].value.len();
+    }
+    if kbd.just_pressed(KeyCode::Back) {
+        if let Some(i) = prev_boundary(&text.sections[state.section].value, state.cursor) {
+            let cursor = state.cursor;
+            text.sections[state.section].value.replace_range(i..cursor, "");
+            state.cursor = i;
+        }
+    }
+    if kbd.just_pressed(KeyCode::Delete) {
+        if let Some(i) = next_boundary(&text.sections[state.section].value, state.cursor) {
+            let cursor = state.cursor;
+            text.sections[state.section].value.replace_range(cursor..i, "");
+        }
+    }
+
+    let mut typed = String::new();
+    if kbd.any_just_pressed([KeyCode::Return, KeyCode::NumpadEnter]) {
+        typed.push('\n');
+    }
+    for c in chars {
+        if !c.is_control() {
+            typed.push(c);
+        }
+    }
+    if !typed.is_empty() {
+        let (section, cursor) = (state.section, state.cursor);
+        text.sections[section].value.insert_str(cursor, &typed);
+        state.cursor += typed.len();
+    }
+}
+
+fn setup_text_caret(
+    mut commands: Commands,
+) {
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::PINK,
+                ..Default::default()
+            },
+            visibility: Visibility::Hidden,
+            ..Default::default()
+        },
+        TextEditCaret,
+        EditorCleanup,
+    ));
+}
+
+fn update_text_caret(
+    time: Res<Time>,
+    state: Res<TextEditState>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_text: Query<(&Text, &TextLayoutInfo, &Text2dBounds, &Anchor, &GlobalTransform)>,
+    mut q_caret: Query<(&mut Transform, &mut Sprite, &mut Visibility), With<TextEditCaret>>,
+) {
+    let Ok((mut xf_caret, mut sprite, mut vis)) = q_caret.get_single_mut() else {
+        return;
+    };
+    let Some((text, layout, bounds, anchor, xf)) = state.entity.and_then(|e| q_text.get(e).ok()) else {
+        *vis = Visibility::Hidden;
+        return;
+    };
+    // blink
+    *vis = if time.elapsed_seconds() % 1.0 < 0.5 {
+        Visibility::Visible
+    } else {
+        Visibility::Hidden
+    };
+
+    let scale = scale_factor(&q_window);
+    let rect = text2d_rect(text, layout, bounds, anchor, scale);
+    let font_size = text.sections.get(state.section).map(|s| s.style.font_size).unwrap_or(16.0);
+
+    // Place the caret before the glyph at the cursor, or after the last glyph before it.
+    // Glyph positions are the centers of the glyphs, relative to the bottom-left of the layout.
+    let mut pos = None;
+    for glyph in &layout.glyphs {
+        if glyph.section_index > state.section {
+            break;
+        }
+        if glyph.section_index == state.section && glyph.byte_index >= state.cursor {
+            pos = Some(glyph.position - Vec2::new(glyph.size.x / 2.0, 0.0));
+            break;
+        }
+        pos = Some(glyph.position + Vec2::new(glyph.size.x / 2.0, 0.0));
+    }
+    let local = match pos {
+        Some(pos) => rect.min + pos / scale,
+        None => Vec2::new(rect.min.x, rect.max.y - font_size / 2.0),
+    };
+
+    sprite.custom_size = Some(Vec2::new((font_size / 12.0).max(1.0), font_size));
+    *xf_caret = Transform::from_matrix(
+        xf.compute_matrix() * Mat4::from_translation(local.extend(0.001))
+    );
+}
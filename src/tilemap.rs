@@ -53,11 +53,15 @@ fn cursor_tilemap_select(
     crs: Res<WorldCursor>,
     mut tm_selected: ResMut<SelectedTilemap>,
     q_tmap: Query<(Entity, &TilemapSize, &TilemapGridSize, &TilemapType, &GlobalTransform)>,
+    q_unpickable: Query<(), crate::lock::Unpickable>,
 ) {
     // TODO: select "through" empty tiles?
 
     let mut closest_z = f32::MIN;
     for (e_tm, size, grid_size, map_type, xf_tm) in &q_tmap {
+        if q_unpickable.contains(e_tm) {
+            continue;
+        }
         if *map_type != TilemapType::Square {
             warn!("Only tilemaps of Square type are currently supported!");
             continue;
@@ -1,16 +1,21 @@
 use crate::crate_prelude::*;
 use crate::assets::EditorAssets;
 use crate::ui::tooltip::TooltipText;
+use std::collections::BTreeMap;
 use std::ops::{BitOr, BitOrAssign};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, States)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, States)]
 #[derive(Reflect, FromReflect)]
+#[derive(Serialize, Deserialize)]
 #[derive(enum_iterator::Sequence)]
 #[repr(u8)]
 pub enum Tool {
     #[default]
     SelectEntities = 0,
     Translation = 1,
+    Rotation = 2,
+    Scale = 3,
     // tilemap tools
     SelectTilemap = 16,
 }
@@ -20,12 +25,14 @@ impl Tool {
         match self {
             Tool::SelectEntities => assets.image_icon_tool_selectentities.clone(),
             Tool::Translation => assets.image_icon_tool_translation.clone(),
+            Tool::Rotation => assets.image_icon_tool_rotation.clone(),
+            Tool::Scale => assets.image_icon_tool_scale.clone(),
             Tool::SelectTilemap => assets.image_icon_tool_selecttilemap.clone(),
         }
     }
 
-    pub(crate) fn tooltip(self) -> TooltipText {
-        match self {
+    pub(crate) fn tooltip(self, hotkeys: &ToolHotkeys) -> TooltipText {
+        let mut tooltip = match self {
             Tool::SelectEntities => TooltipText {
                 title: "Select Entities".into(),
                 text: "Click on entities to select them.\nThen, use other tools to manipulate the selected entities.".into(),
@@ -34,14 +41,69 @@ impl Tool {
                 title: "Move/Translate (Transform Editing)".into(),
                 text: "Move entities with the mouse, changing the translation of their Transform.".into(),
             },
+            Tool::Rotation => TooltipText {
+                title: "Rotate (Transform Editing)".into(),
+                text: "Rotate entities with the mouse, around the center of the selection.".into(),
+            },
+            Tool::Scale => TooltipText {
+                title: "Scale (Transform Editing)".into(),
+                text: "Scale entities uniformly with the mouse, relative to the center of the selection.".into(),
+            },
             Tool::SelectTilemap => TooltipText {
                 title: "Select the Active Tilemap".into(),
                 text: "Tilemap editing tools will operate on the currently selected tilemap.".into(),
             },
+        };
+        let keys = hotkeys.keys_for(self);
+        if !keys.is_empty() {
+            let keys: Vec<_> = keys.iter().map(|key| format!("{:?}", key)).collect();
+            tooltip.text += &format!("\n\nHotkey: {} (hold to use temporarily)", keys.join(" / "));
+        }
+        tooltip
+    }
+}
+
+/// Keyboard shortcuts for switching tools
+///
+/// Tap a key to switch to its tool. Hold it (for longer than `hold_duration`)
+/// to use the tool temporarily, and go back to the previous tool on release.
+///
+/// Saved with the editor settings.
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolHotkeys {
+    pub keys: BTreeMap<Tool, Vec<KeyCode>>,
+    /// Holding a key for longer than this returns to the previous tool on release
+    pub hold_duration: Duration,
+}
+
+impl Default for ToolHotkeys {
+    fn default() -> Self {
+        ToolHotkeys {
+            keys: [
+                (Tool::SelectEntities, vec![KeyCode::V]),
+                (Tool::Translation, vec![KeyCode::G, KeyCode::W]),
+                (Tool::Rotation, vec![KeyCode::R]),
+                (Tool::Scale, vec![KeyCode::S]),
+                (Tool::SelectTilemap, vec![KeyCode::T]),
+            ].into_iter().collect(),
+            hold_duration: Duration::from_millis(250),
         }
     }
 }
 
+impl ToolHotkeys {
+    pub fn keys_for(&self, tool: Tool) -> &[KeyCode] {
+        self.keys.get(&tool).map(|keys| keys.as_slice()).unwrap_or_default()
+    }
+
+    pub fn tool_for(&self, key: KeyCode) -> Option<Tool> {
+        self.keys.iter()
+            .find(|(_, keys)| keys.contains(&key))
+            .map(|(tool, _)| *tool)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Tools(u64);
 
@@ -0,0 +1,495 @@
+//! Transform editing tools (move/rotate/scale selected entities with the mouse)
+//!
+//! All of these operate on everything that is currently `Selected`, by
+//! click-and-drag anywhere in the editor view. Rotation and scaling pivot
+//! around the center of the selection.
+//!
+//! Snapping (to a grid, angle increments, scale steps) is configured via
+//! the `SnapSettings` resource and can be temporarily inverted by holding
+//! Alt while dragging.
+//!
+//! Independently of the active tool, the arrow keys nudge the selected
+//! entities by one pixel (Ctrl: ten pixels, Shift: one grid cell).
+
+use serde::{Deserialize, Serialize};
+
+use crate::crate_prelude::*;
+use crate::camera::{WorldCursor, WorldCursorSet};
+use crate::history::{EditorHistory, HistoryStep, TransformChange};
+use crate::selection::Selected;
+
+pub(crate) struct TransformEditorPlugin<S: States> {
+    pub state: S,
+}
+
+impl<S: States> Plugin for TransformEditorPlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SnapSettings>();
+        app.init_resource::<SnapGrid>();
+        app.add_system(
+            update_snap_grid
+                .in_set(EditorSet)
+        );
+        app.add_system(
+            transform_drag
+                .in_set(EditorSet)
+                .after(WorldCursorSet)
+                .after(update_snap_grid)
+                .run_if(with_tools(Tool::Translation | Tool::Rotation | Tool::Scale))
+        );
+        app.add_system(
+            keyboard_nudge
+                .in_set(EditorSet)
+                .after(update_snap_grid)
+                .run_if(crate::text::not_editing_text)
+        );
+        app.add_system(
+            update_snap_toggle_labels
+                .in_set(EditorSet)
+                .run_if(resource_changed::<SnapSettings>())
+        );
+    }
+}
+
+/// Configuration for snapping when using the transform tools
+///
+/// Saved with the editor settings.
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnapSettings {
+    /// Snap positions to the grid
+    pub translation: bool,
+    /// Grid size for snapping positions, in world units
+    pub grid: Vec2,
+    /// Use the grid of the currently selected tilemap, if any, instead of `grid`
+    pub grid_from_tilemap: bool,
+    /// Snap rotations to increments of `rotation_step`
+    pub rotation: bool,
+    /// Angle increment for snapping rotations, in radians
+    pub rotation_step: f32,
+    /// Snap scale to multiples of `scale_step`
+    pub scale: bool,
+    pub scale_step: f32,
+}
+
+impl Default for SnapSettings {
+    fn default() -> Self {
+        SnapSettings {
+            translation: false,
+            grid: Vec2::new(16.0, 16.0),
+            grid_from_tilemap: true,
+            rotation: false,
+            rotation_step: 15f32.to_radians(),
+            scale: false,
+            scale_step: 0.25,
+        }
+    }
+}
+
+/// Holding this inverts the snapping settings while dragging
+const SNAP_INVERT_KEYS: [KeyCode; 2] = [KeyCode::LAlt, KeyCode::RAlt];
+
+/// The grid to snap positions to: grid cell size in some coordinate space
+///
+/// Computed from the `SnapSettings` (and the selected tilemap, if any).
+#[derive(Resource)]
+pub(crate) struct SnapGrid {
+    pub step: Vec2,
+    pub to_world: Mat4,
+}
+
+impl Default for SnapGrid {
+    fn default() -> Self {
+        SnapGrid {
+            step: SnapSettings::default().grid,
+            to_world: Mat4::IDENTITY,
+        }
+    }
+}
+
+impl SnapGrid {
+    /// The size of one grid cell, as a vector in world space
+    pub fn world_step(&self) -> Vec2 {
+        let v = self.to_world.transform_vector3(self.step.extend(0.0));
+        Vec2::new(v.x.abs(), v.y.abs())
+    }
+
+    fn snap(&self, world_pos: Vec3) -> Vec3 {
+        let local = self.to_world.inverse().transform_point3(world_pos);
+        let snapped = (local.truncate() / self.step).round() * self.step;
+        self.to_world.transform_point3(snapped.extend(local.z))
+    }
+}
+
+/// Per-entity state remembered when a drag starts
+struct DragEntity {
+    entity: Entity,
+    start: Transform,
+    start_local: Transform,
+    parent: Option<Entity>,
+}
+
+struct DragState {
+    start_cursor: Vec2,
+    pivot: Vec2,
+    entities: Vec<DragEntity>,
+}
+
+fn update_snap_grid(
+    settings: Res<SnapSettings>,
+    mut grid: ResMut<SnapGrid>,
+    #[cfg(feature = "bevy_ecs_tilemap")]
+    tm_selected: Res<crate::tilemap::SelectedTilemap>,
+    #[cfg(feature = "bevy_ecs_tilemap")]
+    q_tilemap: Query<(&bevy_ecs_tilemap::prelude::TilemapGridSize, &GlobalTransform)>,
+) {
+    let mut new = SnapGrid {
+        step: settings.grid,
+        to_world: Mat4::IDENTITY,
+    };
+    #[cfg(feature = "bevy_ecs_tilemap")]
+    if settings.grid_from_tilemap {
+        if let Some((grid_size, xf_tm)) = tm_selected.entity.and_then(|e| q_tilemap.get(e).ok()) {
+            new.step = Vec2::new(grid_size.x, grid_size.y);
+            new.to_world = xf_tm.compute_matrix();
+        }
+    }
+    if new.step.x <= 0.0 || new.step.y <= 0.0 {
+        new.step = Vec2::ONE;
+    }
+    *grid = new;
+}
+
+/// Convert a desired world-space transform into the local space of the parent (if any)
+pub(crate) fn world_to_local(target: Transform, parent: Option<&GlobalTransform>) -> Transform {
+    if let Some(xf_parent) = parent {
+        Transform::from_matrix(xf_parent.compute_matrix().inverse() * target.compute_matrix())
+    } else {
+        target
+    }
+}
+
+/// Whether the entity has a selected ancestor, and so moves along with it
+fn has_selected_ancestor(
+    mut e: Entity,
+    q_parent: &Query<&Parent>,
+    q_is_selected: &Query<(), With<Selected>>,
+) -> bool {
+    while let Ok(parent) = q_parent.get(e) {
+        e = parent.get();
+        if q_is_selected.contains(e) {
+            return true;
+        }
+    }
+    false
+}
+
+fn transform_drag(
+    tool: Res<State<Tool>>,
+    settings: Res<SnapSettings>,
+    grid: Res<SnapGrid>,
+    mousebutt: Res<Input<MouseButton>>,
+    kbd: Res<Input<KeyCode>>,
+    crs: Res<WorldCursor>,
+    mut history: ResMut<EditorHistory>,
+    mut drag: Local<Option<DragState>>,
+    mut q_selected: Query<(Entity, &mut Transform, &GlobalTransform, Option<&Parent>), With<Selected>>,
+    q_global: Query<&GlobalTransform>,
+    q_parent: Query<&Parent>,
+    q_is_selected: Query<(), With<Selected>>,
+    q_ui: Query<&Interaction, With<Node>>,
+) {
+    // TODO: transition to a proper input mgmt framework like LWIM
+    // clicks on the UI (toolbar, panels, widgets) must not start a drag
+    if mousebutt.just_pressed(MouseButton::Left) && crate::ui::cursor_not_over_ui(q_ui) {
+        // children of selected entities follow their parent through the hierarchy
+        let entities: Vec<_> = q_selected.iter()
+            .filter(|(entity, _, _, _)| !has_selected_ancestor(*entity, &q_parent, &q_is_selected))
+            .map(|(entity, xf, xf_global, parent)| DragEntity {
+                entity,
+                start: xf_global.compute_transform(),
+                start_local: *xf,
+                parent: parent.map(|p| p.get()),
+            })
+            .collect();
+        if entities.is_empty() {
+            *drag = None;
+            return;
+        }
+        let pivot = entities.iter()
+            .map(|de| de.start.translation.truncate())
+            .sum::<Vec2>() / entities.len() as f32;
+        *drag = Some(DragState {
+            start_cursor: crs.pos,
+            pivot,
+            entities,
+        });
+    }
+    if !mousebutt.pressed(MouseButton::Left) {
+        // the drag is over, record it as one step in the history
+        if let Some(drag) = drag.take() {
+            history.push(HistoryStep {
+                transforms: drag.entities.iter()
+                    .filter_map(|de| {
+                        let (_, xf, _, _) = q_selected.get(de.entity).ok()?;
+                        (*xf != de.start_local).then(|| TransformChange {
+                            entity: de.entity,
+                            before: de.start_local,
+                            after: *xf,
+                        })
+                    })
+                    .collect(),
+            });
+        }
+        return;
+    }
+    let Some(drag) = &*drag else {
+        return;
+    };
+
+    let invert = kbd.any_pressed(SNAP_INVERT_KEYS);
+
+    let start_offset = drag.start_cursor - drag.pivot;
+    let offset = crs.pos - drag.pivot;
+
+    for de in &drag.entities {
+        let mut target = de.start;
+        match tool.0 {
+            Tool::Translation => {
+                target.translation += (crs.pos - drag.start_cursor).extend(0.0);
+                if settings.translation != invert {
+                    target.translation = grid.snap(target.translation);
+                }
+            }
+            Tool::Rotation => {
+                if start_offset == Vec2::ZERO || offset == Vec2::ZERO {
+                    continue;
+                }
+                let mut angle = start_offset.angle_between(offset);
+                if settings.rotation != invert && settings.rotation_step > 0.0 {
+                    angle = (angle / settings.rotation_step).round() * settings.rotation_step;
+                }
+                let rot = Quat::from_rotation_z(angle);
+                let rel = de.start.translation.truncate() - drag.pivot;
+                target.translation = (drag.pivot + (rot * rel.extend(0.0)).truncate())
+                    .extend(de.start.translation.z);
+                target.rotation = rot * de.start.rotation;
+            }
+            Tool::Scale => {
+                let start_dist = start_offset.length();
+                if start_dist < f32::EPSILON {
+                    continue;
+                }
+                let mut ratio = offset.length() / start_dist;
+                if settings.scale != invert && settings.scale_step > 0.0 && de.start.scale.x != 0.0 {
+                    // snap the magnitude, so mirrored entities stay mirrored
+                    let step = settings.scale_step;
+                    let snapped = ((de.start.scale.x * ratio).abs() / step).round() * step;
+                    // don't collapse small entities to zero
+                    if snapped > 0.0 {
+                        ratio = snapped / de.start.scale.x.abs();
+                    }
+                }
+                let rel = de.start.translation.truncate() - drag.pivot;
+                target.translation = (drag.pivot + rel * ratio).extend(de.start.translation.z);
+                target.scale.x = de.start.scale.x * ratio;
+                target.scale.y = de.start.scale.y * ratio;
+            }
+            _ => {}
+        }
+
+        // we computed where we want the entity to be in world space
+        let local = world_to_local(target, de.parent.and_then(|p| q_global.get(p).ok()));
+        if let Ok((_, mut xf, _, _)) = q_selected.get_mut(de.entity) {
+            *xf = local;
+        }
+    }
+}
+
+/// Delay before a held arrow key starts repeating
+const NUDGE_REPEAT_DELAY: Duration = Duration::from_millis(300);
+/// Interval between repeats while an arrow key is held
+const NUDGE_REPEAT_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Default)]
+struct NudgeState {
+    /// Transforms before the nudging started, for the history
+    before: Vec<(Entity, Transform)>,
+    repeat: Option<Timer>,
+}
+
+fn keyboard_nudge(
+    time: Res<Time>,
+    kbd: Res<Input<KeyCode>>,
+    grid: Res<SnapGrid>,
+    mut history: ResMut<EditorHistory>,
+    mut state: Local<NudgeState>,
+    mut q_selected: Query<(Entity, &mut Transform, Option<&Parent>), With<Selected>>,
+    q_global: Query<&GlobalTransform>,
+    q_parent: Query<&Parent>,
+    q_is_selected: Query<(), With<Selected>>,
+) {
+    // TODO: transition to a proper input mgmt framework like LWIM
+    const ARROWS: [KeyCode; 4] = [KeyCode::Left, KeyCode::Right, KeyCode::Up, KeyCode::Down];
+
+    if !kbd.any_pressed(ARROWS) {
+        // all keys released, record everything as one step in the history
+        if !state.before.is_empty() {
+            history.push(HistoryStep {
+                transforms: state.before.drain(..)
+                    .filter_map(|(entity, before)| {
+                        let (_, xf, _) = q_selected.get(entity).ok()?;
+                        (*xf != before).then(|| TransformChange {
+                            entity,
+                            before,
+                            after: *xf,
+                        })
+                    })
+                    .collect(),
+            });
+        }
+        state.repeat = None;
+        return;
+    }
+
+    // move on the initial press, and then repeatedly while held
+    let go = if kbd.any_just_pressed(ARROWS) {
+        state.repeat = Some(Timer::new(NUDGE_REPEAT_DELAY, TimerMode::Once));
+        true
+    } else if let Some(timer) = &mut state.repeat {
+        timer.tick(time.delta());
+        if timer.just_finished() {
+            *timer = Timer::new(NUDGE_REPEAT_INTERVAL, TimerMode::Once);
+            true
+        } else {
+            false
+        }
+    } else {
+        false
+    };
+    if !go {
+        return;
+    }
+
+    let mut dir = Vec2::ZERO;
+    if kbd.pressed(KeyCode::Left) {
+        dir.x -= 1.0;
+    }
+    if kbd.pressed(KeyCode::Right) {
+        dir.x += 1.0;
+    }
+    if kbd.pressed(KeyCode::Down) {
+        dir.y -= 1.0;
+    }
+    if kbd.pressed(KeyCode::Up) {
+        dir.y += 1.0;
+    }
+    let delta = if kbd.any_pressed([KeyCode::LShift, KeyCode::RShift]) {
+        dir * grid.world_step()
+    } else if kbd.any_pressed([KeyCode::LControl, KeyCode::RControl]) {
+        dir * 10.0
+    } else {
+        dir
+    };
+    if delta == Vec2::ZERO {
+        return;
+    }
+
+    for (entity, mut xf, parent) in &mut q_selected {
+        if has_selected_ancestor(entity, &q_parent, &q_is_selected) {
+            continue;
+        }
+        if !state.before.iter().any(|(e, _)| *e == entity) {
+            state.before.push((entity, *xf));
+        }
+        // only touch the translation, so that rotation/scale don't accumulate float error
+        let local_delta = match parent.and_then(|p| q_global.get(p.get()).ok()) {
+            Some(xf_parent) => xf_parent.compute_matrix().inverse().transform_vector3(delta.extend(0.0)),
+            None => delta.extend(0.0),
+        };
+        xf.translation += local_delta;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SnapKind {
+    Translation,
+    Rotation,
+    Scale,
+}
+
+impl SnapKind {
+    fn label(self, settings: &SnapSettings) -> String {
+        let (enabled, name) = match self {
+            SnapKind::Translation => (settings.translation, "Position"),
+            SnapKind::Rotation => (settings.rotation, "Rotation"),
+            SnapKind::Scale => (settings.scale, "Scale"),
+        };
+        format!("[{}] {}", if enabled { "x" } else { " " }, name)
+    }
+}
+
+/// Marker for the buttons in the Tool Options panel that toggle snapping
+#[derive(Component, Clone)]
+struct SnapToggle(SnapKind);
+
+/// Populate a panel with the buttons for toggling snapping
+pub(crate) fn spawn_snap_options(
+    commands: &mut Commands,
+    assets: &EditorAssets,
+    settings: &SnapSettings,
+    parent: Entity,
+) {
+    for kind in [SnapKind::Translation, SnapKind::Rotation, SnapKind::Scale] {
+        let butt = commands.spawn((
+            ButtonBundle {
+                background_color: BackgroundColor(Color::NONE),
+                style: Style {
+                    padding: UiRect::all(Val::Px(1.0)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            SnapToggle(kind),
+            ClickBehavior::new().entity_system(snap_toggle_handler),
+        )).id();
+        let text = commands.spawn((
+            TextBundle {
+                text: Text::from_section(kind.label(settings), TextStyle {
+                    font: assets.font.clone(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ThemeText::default(),
+        )).id();
+        commands.entity(butt).push_children(&[text]);
+        commands.entity(parent).push_children(&[butt]);
+    }
+}
+
+fn snap_toggle_handler(
+    In(entity): In<Entity>,
+    q_butt: Query<&SnapToggle>,
+    mut settings: ResMut<SnapSettings>,
+) {
+    let Ok(toggle) = q_butt.get(entity) else { return; };
+    match toggle.0 {
+        SnapKind::Translation => settings.translation = !settings.translation,
+        SnapKind::Rotation => settings.rotation = !settings.rotation,
+        SnapKind::Scale => settings.scale = !settings.scale,
+    }
+}
+
+fn update_snap_toggle_labels(
+    settings: Res<SnapSettings>,
+    q_butt: Query<(&SnapToggle, &Children)>,
+    mut q_text: Query<&mut Text>,
+) {
+    for (toggle, children) in &q_butt {
+        let mut iter = q_text.iter_many_mut(children.iter());
+        if let Some(mut text) = iter.fetch_next() {
+            text.sections[0].value = toggle.0.label(&settings);
+        }
+    }
+}
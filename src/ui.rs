@@ -4,6 +4,10 @@ pub(crate) mod toolbar;
 pub(crate) mod tooltip;
 pub(crate) mod panel;
 pub(crate) mod menu;
+pub(crate) mod candidates;
+pub(crate) mod dock;
+pub mod widget;
+pub mod theme;
 
 pub(crate) struct EditorUiPlugin<S: States> {
     pub state: S,
@@ -24,10 +28,70 @@ impl<S: States> Plugin for EditorUiPlugin<S> {
         app.add_plugin(menu::MenuPlugin {
             state: self.state.clone(),
         });
+        app.add_plugin(candidates::CandidateListPlugin {
+            state: self.state.clone(),
+        });
+        app.add_plugin(dock::DockPlugin {
+            state: self.state.clone(),
+        });
+        app.add_plugin(theme::ThemePlugin {
+            state: self.state.clone(),
+        });
+        app.add_plugin(widget::WidgetPlugin {
+            state: self.state.clone(),
+        });
+        app.init_resource::<UiTextInputActive>();
         app.add_system(simple_butt_visual.in_set(EditorSet));
     }
 }
 
+/// Which editor UI (if any) is taking keyboard input as text
+///
+/// Keyboard shortcuts are disabled while this is set (see `not_editing_text`).
+/// Only one owner at a time: taking the input away from another owner makes
+/// it stop editing.
+#[derive(Resource, Default)]
+pub struct UiTextInputActive(pub Option<UiTextInputOwner>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UiTextInputOwner {
+    LayerRename,
+    Widget,
+}
+
+impl UiTextInputActive {
+    pub fn is_active(&self) -> bool {
+        self.0.is_some()
+    }
+
+    pub fn is_owner(&self, owner: UiTextInputOwner) -> bool {
+        self.0 == Some(owner)
+    }
+
+    /// Start taking text input (the previous owner, if any, stops editing)
+    pub fn take(&mut self, owner: UiTextInputOwner) {
+        self.0 = Some(owner);
+    }
+
+    /// Stop taking text input, unless another owner has taken it already
+    pub fn release(&mut self, owner: UiTextInputOwner) {
+        if self.is_owner(owner) {
+            self.0 = None;
+        }
+    }
+}
+
+/// Run condition: the mouse cursor is not over any interactive editor UI
+///
+/// Use for things that should only react to the mouse when it is over the world.
+/// UI nodes only count if they have an `Interaction`, so give one to any node
+/// that should stop clicks (such as the background of a panel or popup).
+pub(crate) fn cursor_not_over_ui(
+    q_interaction: Query<&Interaction, With<Node>>,
+) -> bool {
+    q_interaction.iter().all(|i| *i == Interaction::None)
+}
+
 #[derive(Component)]
 struct SimpleButtVisual;
 
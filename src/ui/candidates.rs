@@ -0,0 +1,237 @@
+//! Popup next to the cursor, listing all the selection candidates under it
+//!
+//! Shown only when there are multiple overlapping candidates. The pending
+//! one (the one that will be selected on click; cycle with the mouse wheel)
+//! is highlighted. Clicking an entry in the list selects it directly.
+//!
+//! The popup does not follow the cursor around, so that it is possible to
+//! move the mouse over it. When the candidates change, it waits a little
+//! before moving/updating, and while the mouse is over it, it stays as is.
+
+use bevy::sprite::Mesh2dHandle;
+use bevy::text::Text2dBounds;
+use bevy::window::PrimaryWindow;
+
+use crate::crate_prelude::*;
+use crate::selection::{SelectionCandidateEvent, SelectionCandidates, SelectionPending};
+
+/// How far from the cursor to put the popup, in pixels
+const POPUP_OFFSET: f32 = 16.0;
+
+/// How long to keep showing outdated candidates, to allow moving the mouse to the popup
+const POPUP_LINGER: Duration = Duration::from_millis(400);
+
+pub(crate) struct CandidateListPlugin<S: States> {
+    pub state: S,
+}
+
+impl<S: States> Plugin for CandidateListPlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            despawn_all_recursive::<With<CandidateList>>
+                .in_schedule(OnExit(Tool::SelectEntities))
+        );
+        app.add_system(
+            update_candidate_list
+                .in_set(EditorSet)
+                .after(crate::selection::SelectionCandidateSet)
+                .run_if(resource_exists::<SelectionCandidates>())
+        );
+    }
+}
+
+/// The root node of the popup
+#[derive(Component)]
+struct CandidateList {
+    /// What is currently displayed, so we know when to rebuild
+    shown: Vec<Entity>,
+    pending: Option<Entity>,
+    /// When the candidates changed from what is shown
+    outdated_since: Option<Duration>,
+}
+
+/// An entry in the popup
+#[derive(Component)]
+struct CandidateListRow(Entity);
+
+/// Text to describe an entity in the list: its `Name`, or what kind of thing it is
+fn candidate_label(
+    e: Entity,
+    q_info: &Query<(Option<&Name>, Option<&Sprite>, Option<&TextureAtlasSprite>, Option<&Text2dBounds>, Option<&Mesh2dHandle>)>,
+) -> String {
+    let Ok((name, sprite, atlas, text, mesh)) = q_info.get(e) else {
+        return format!("{:?}", e);
+    };
+    if let Some(name) = name {
+        return name.as_str().to_owned();
+    }
+    let kind = if sprite.is_some() {
+        "Sprite"
+    } else if atlas.is_some() {
+        "Atlas Sprite"
+    } else if text.is_some() {
+        "Text"
+    } else if mesh.is_some() {
+        "Mesh"
+    } else {
+        "Entity"
+    };
+    format!("{} {:?}", kind, e)
+}
+
+fn popup_position(window: &Window) -> UiRect {
+    let Some(cursor) = window.cursor_position() else {
+        return UiRect::all(Val::Auto);
+    };
+    // keep it on-screen, on whichever side of the cursor has more space
+    let mut rect = UiRect::all(Val::Auto);
+    if cursor.x < window.width() / 2.0 {
+        rect.left = Val::Px(cursor.x + POPUP_OFFSET);
+    } else {
+        rect.right = Val::Px(window.width() - cursor.x + POPUP_OFFSET);
+    }
+    if cursor.y < window.height() / 2.0 {
+        rect.bottom = Val::Px(cursor.y + POPUP_OFFSET);
+    } else {
+        rect.top = Val::Px(window.height() - cursor.y + POPUP_OFFSET);
+    }
+    rect
+}
+
+fn update_candidate_list(
+    mut commands: Commands,
+    time: Res<Time>,
+    assets: Res<EditorAssets>,
+    candidates: Res<SelectionCandidates>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_pending: Query<&SelectionPending>,
+    q_xf: Query<&GlobalTransform>,
+    q_info: Query<(Option<&Name>, Option<&Sprite>, Option<&TextureAtlasSprite>, Option<&Text2dBounds>, Option<&Mesh2dHandle>)>,
+    q_interaction: Query<&Interaction>,
+    mut q_popup: Query<(Entity, &mut CandidateList, &mut Style, Option<&Children>)>,
+) {
+    let popup = q_popup.get_single_mut().ok();
+
+    // don't move things around while the user is trying to click on them
+    if let Some((e, _, _, children)) = &popup {
+        let hovered = q_interaction.get(*e).map(|i| *i != Interaction::None).unwrap_or(false) ||
+            children.map(|c| q_interaction.iter_many(c.iter()).any(|i| *i != Interaction::None)).unwrap_or(false);
+        if hovered {
+            return;
+        }
+    }
+
+    // topmost first
+    let mut list: Vec<(f32, Entity)> = candidates.candidates.keys()
+        .filter_map(|e| q_xf.get(*e).ok().map(|xf| (xf.translation().z, *e)))
+        .collect();
+    list.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let list: Vec<Entity> = list.into_iter().map(|(_, e)| e).collect();
+    let pending = q_pending.get_single().ok().and_then(|p| p.target);
+
+    let now = time.elapsed();
+    let position = q_window.get_single().map(popup_position).unwrap_or(UiRect::all(Val::Auto));
+
+    let e_popup = match popup {
+        Some((e, mut popup, mut style, _)) => {
+            if popup.shown == list {
+                popup.outdated_since = None;
+                if popup.pending == pending {
+                    return;
+                }
+            } else {
+                let since = *popup.outdated_since.get_or_insert(now);
+                if now - since < POPUP_LINGER {
+                    return;
+                }
+                if list.len() < 2 {
+                    commands.entity(e).despawn_recursive();
+                    return;
+                }
+                popup.outdated_since = None;
+                style.position = position;
+            }
+            popup.shown = list.clone();
+            popup.pending = pending;
+            commands.entity(e).despawn_descendants();
+            e
+        }
+        None if list.len() < 2 => {
+            return;
+        }
+        None => {
+            commands.spawn((
+                NodeBundle {
+                    focus_policy: FocusPolicy::Block,
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position,
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Stretch,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                Interaction::default(),
+                ThemeBackground(|t| t.colors.candidates_border),
+                ThemePadding(|t| UiRect::all(Val::Px(t.spacing.candidates))),
+                ThemeZIndex(|t| t.layers.candidates),
+                CandidateList {
+                    shown: list.clone(),
+                    pending,
+                    outdated_since: None,
+                },
+                EditorCleanup,
+            )).id()
+        }
+    };
+
+    for e in list {
+        let row = commands.spawn((
+            ButtonBundle::default(),
+            CandidateListRow(e),
+            ThemeBackground(if Some(e) == pending {
+                |t| t.colors.candidates_pending
+            } else {
+                |t| t.colors.candidates_row
+            }),
+            ThemePadding(|t| UiRect::all(Val::Px(t.spacing.candidates))),
+            ClickBehavior::new().entity_system(candidate_row_handler),
+        )).id();
+        let text = commands.spawn((
+            TextBundle {
+                focus_policy: FocusPolicy::Pass,
+                text: Text::from_section(candidate_label(e, &q_info), TextStyle {
+                    font: assets.font.clone(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ThemeText::default(),
+        )).id();
+        commands.entity(row).push_children(&[text]);
+        commands.entity(e_popup).push_children(&[row]);
+    }
+}
+
+fn candidate_row_handler(
+    In(entity): In<Entity>,
+    mut commands: Commands,
+    q_row: Query<&CandidateListRow>,
+    candidates: Option<Res<SelectionCandidates>>,
+    mut q_pending: Query<&mut SelectionPending>,
+    mut evw_candidate: EventWriter<SelectionCandidateEvent>,
+) {
+    let Ok(row) = q_row.get(entity) else { return; };
+    let Some((color, bounds)) = candidates.and_then(|c| c.candidates.get(&row.0).copied()) else {
+        return;
+    };
+    let target = row.0;
+    commands.add(move |world: &mut World| {
+        crate::selection::select_entity(world, target, bounds, color.with_a(0.5));
+    });
+    for mut pending in &mut q_pending {
+        pending.target = None;
+    }
+    evw_candidate.send(SelectionCandidateEvent::Remove { entity: target });
+}
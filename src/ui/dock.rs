@@ -0,0 +1,865 @@
+//! Docking panels to the edges of the screen
+//!
+//! Drag a panel by its titlebar to an edge of the screen to dock it there.
+//! Drop it onto a docked panel to add it as a tab (middle), or to split the
+//! space with it (near either end). Dragging a docked panel by its titlebar
+//! undocks it again. The splitters at the inner edge of each dock area and
+//! between docked panels can be dragged to resize them.
+//!
+//! The editor camera's viewport is shrunk to the area not covered by docks,
+//! so docked panels never cover the world being edited.
+
+use bevy::input::mouse::MouseMotion;
+use bevy::render::camera::Viewport;
+use bevy::window::PrimaryWindow;
+
+use crate::crate_prelude::*;
+use crate::camera::EditorCamera;
+use crate::settings::{DockAreaSettings, DockGroupSettings, DockSettings, EditorSettings, SettingsCollectSet, SettingsRestoreSet};
+
+use super::panel::{PanelEntity, PanelTitlebarEntity};
+
+/// How close to the edge of the screen to drop a panel to dock it, in pixels
+const EDGE_SNAP: f32 = 32.0;
+/// Fraction of a docked panel's length, at each end, where dropping splits instead of making a tab
+const SPLIT_ZONE: f32 = 0.25;
+/// How far to drag a docked panel before it undocks, in pixels
+const UNDOCK_DISTANCE: f32 = 8.0;
+const DEFAULT_DOCK_SIZE: f32 = 256.0;
+const MIN_DOCK_SIZE: f32 = 64.0;
+const SPLITTER_SIZE: f32 = 4.0;
+const TAB_HEIGHT: f32 = 18.0;
+
+pub(crate) struct DockPlugin<S: States> {
+    pub state: S,
+}
+
+impl<S: States> Plugin for DockPlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DockLayout>();
+        app.init_resource::<DockRects>();
+        app.add_systems(
+            (
+                setup_dock_preview,
+            ).in_schedule(OnEnter(self.state.clone()))
+        );
+        app.add_systems(
+            (
+                reset_dock_layout,
+                reset_editor_viewport,
+            ).in_schedule(OnExit(self.state.clone()))
+        );
+        app.add_systems(
+            (
+                dock_panel_drag.before(super::panel::panel_titlebar_drag),
+                dock_splitter_drag,
+                apply_dock_layout
+                    .after(dock_panel_drag)
+                    .after(dock_splitter_drag),
+                update_dock_decorations.after(apply_dock_layout),
+                update_editor_viewport
+                    .after(apply_dock_layout)
+                    .before(crate::camera::WorldCursorSet),
+            ).in_set(EditorSet)
+        );
+        app.add_system(
+            restore_dock_settings
+                .in_set(SettingsRestoreSet)
+                .after(super::panel::spawn_registered_panels)
+                .before(apply_dock_layout)
+        );
+        app.add_system(
+            collect_dock_settings
+                .in_set(SettingsCollectSet)
+        );
+    }
+}
+
+/// An edge of the screen where panels can be docked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum DockEdge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl DockEdge {
+    const ALL: [DockEdge; 4] = [DockEdge::Left, DockEdge::Right, DockEdge::Top, DockEdge::Bottom];
+
+    /// Are the docked panels stacked vertically?
+    fn vertical(self) -> bool {
+        matches!(self, DockEdge::Left | DockEdge::Right)
+    }
+}
+
+/// Panels sharing the same space, as tabs
+#[derive(Debug, Clone)]
+struct DockGroup {
+    panels: Vec<Entity>,
+    active: usize,
+    /// Relative share of the dock area's length
+    weight: f32,
+}
+
+impl DockGroup {
+    fn new(panel: Entity, weight: f32) -> Self {
+        DockGroup {
+            panels: vec![panel],
+            active: 0,
+            weight,
+        }
+    }
+}
+
+/// Everything docked to one edge of the screen
+#[derive(Debug, Clone)]
+struct DockArea {
+    /// Width (or height, for top/bottom) in pixels
+    size: f32,
+    groups: Vec<DockGroup>,
+}
+
+/// What panels are docked where
+#[derive(Resource, Debug, Clone)]
+pub(crate) struct DockLayout {
+    /// Indexed by `DockEdge`
+    areas: [DockArea; 4],
+}
+
+impl Default for DockLayout {
+    fn default() -> Self {
+        let area = DockArea {
+            size: DEFAULT_DOCK_SIZE,
+            groups: vec![],
+        };
+        DockLayout {
+            areas: [area.clone(), area.clone(), area.clone(), area],
+        }
+    }
+}
+
+/// Where to dock a panel
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DockTarget {
+    /// At the end of a dock area
+    Edge(DockEdge),
+    /// As a tab in an existing group
+    Tab(DockEdge, usize),
+    /// As a new group before/after an existing one
+    Split(DockEdge, usize, bool),
+}
+
+impl DockLayout {
+    fn area(&self, edge: DockEdge) -> &DockArea {
+        &self.areas[edge as usize]
+    }
+
+    fn area_mut(&mut self, edge: DockEdge) -> &mut DockArea {
+        &mut self.areas[edge as usize]
+    }
+
+    fn panels(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.areas.iter()
+            .flat_map(|a| a.groups.iter())
+            .flat_map(|g| g.panels.iter().copied())
+    }
+
+    /// Is the panel docked?
+    pub(crate) fn is_docked(&self, panel: Entity) -> bool {
+        self.panels().any(|e| e == panel)
+    }
+
+    /// Remove panels from the layout, cleaning up any empty groups
+    fn retain(&mut self, mut f: impl FnMut(Entity) -> bool) {
+        for area in &mut self.areas {
+            for group in &mut area.groups {
+                group.panels.retain(|e| f(*e));
+                group.active = group.active.min(group.panels.len().saturating_sub(1));
+            }
+            area.groups.retain(|g| !g.panels.is_empty());
+        }
+    }
+
+    fn undock(&mut self, panel: Entity) {
+        self.retain(|e| e != panel);
+    }
+
+    fn dock(&mut self, panel: Entity, target: DockTarget) {
+        if self.is_docked(panel) {
+            return;
+        }
+        match target {
+            DockTarget::Edge(edge) => {
+                let area = self.area_mut(edge);
+                area.groups.push(DockGroup::new(panel, 1.0));
+            }
+            DockTarget::Tab(edge, i) => {
+                let area = self.area_mut(edge);
+                if let Some(group) = area.groups.get_mut(i) {
+                    group.panels.push(panel);
+                    group.active = group.panels.len() - 1;
+                } else {
+                    area.groups.push(DockGroup::new(panel, 1.0));
+                }
+            }
+            DockTarget::Split(edge, i, after) => {
+                let area = self.area_mut(edge);
+                // take half of the space of the group we split
+                let weight = if let Some(group) = area.groups.get_mut(i) {
+                    group.weight /= 2.0;
+                    group.weight
+                } else {
+                    1.0
+                };
+                let i = (if after { i + 1 } else { i }).min(area.groups.len());
+                area.groups.insert(i, DockGroup::new(panel, weight));
+            }
+        }
+    }
+}
+
+fn reset_dock_layout(
+    mut layout: ResMut<DockLayout>,
+) {
+    // the panels are despawned when exiting the editor
+    *layout = DockLayout::default();
+}
+
+/// Dock the panels as they were saved, matching them by title
+fn restore_dock_settings(
+    settings: Res<EditorSettings>,
+    mut layout: ResMut<DockLayout>,
+    q_panel: Query<(Entity, &PanelEntity)>,
+) {
+    let find = |title: &String| {
+        q_panel.iter()
+            .find(|(_, panel)| panel.title == *title)
+            .map(|(e, _)| e)
+    };
+    for (area, saved) in layout.areas.iter_mut().zip(&settings.dock.areas) {
+        area.size = saved.size.max(MIN_DOCK_SIZE);
+        area.groups = saved.groups.iter().filter_map(|group| {
+            let panels: Vec<_> = group.panels.iter().filter_map(find).collect();
+            if panels.is_empty() {
+                return None;
+            }
+            Some(DockGroup {
+                active: group.active.min(panels.len() - 1),
+                panels,
+                weight: if group.weight > 0.0 { group.weight } else { 1.0 },
+            })
+        }).collect();
+    }
+}
+
+fn collect_dock_settings(
+    layout: Res<DockLayout>,
+    mut settings: ResMut<EditorSettings>,
+    q_panel: Query<&PanelEntity>,
+) {
+    if !layout.is_changed() {
+        return;
+    }
+    let dock = DockSettings {
+        areas: layout.areas.iter().map(|area| DockAreaSettings {
+            size: area.size,
+            groups: area.groups.iter().map(|group| DockGroupSettings {
+                panels: group.panels.iter()
+                    .filter_map(|e| q_panel.get(*e).ok())
+                    .map(|panel| panel.title.clone())
+                    .collect(),
+                active: group.active,
+                weight: group.weight,
+            }).collect(),
+        }).collect(),
+    };
+    if settings.dock != dock {
+        settings.dock = dock;
+    }
+}
+
+/// Where the docked things are on screen, in UI coordinates
+#[derive(Resource, Default)]
+struct DockRects {
+    /// Indexed by `DockEdge`; `None` if nothing is shown there
+    areas: [Option<Rect>; 4],
+    /// The groups that have shown panels: (edge, group index, rect)
+    groups: Vec<(DockEdge, usize, Rect)>,
+    /// The area not covered by docks
+    free: Rect,
+}
+
+fn compute_rects(
+    layout: &DockLayout,
+    window_size: Vec2,
+    is_shown: impl Fn(Entity) -> bool,
+) -> DockRects {
+    let group_shown = |g: &DockGroup| g.panels.iter().any(|e| is_shown(*e));
+    let mut sizes = [0.0; 4];
+    for edge in DockEdge::ALL {
+        let area = layout.area(edge);
+        if area.groups.iter().any(group_shown) {
+            let max = if edge.vertical() { window_size.x } else { window_size.y } / 2.0;
+            sizes[edge as usize] = area.size.clamp(MIN_DOCK_SIZE, max.max(MIN_DOCK_SIZE));
+        }
+    }
+    let [l, r, t, b] = sizes;
+    let area_rects = [
+        Rect::new(0.0, t, l, window_size.y - b),
+        Rect::new(window_size.x - r, t, window_size.x, window_size.y - b),
+        Rect::new(0.0, 0.0, window_size.x, t),
+        Rect::new(0.0, window_size.y - b, window_size.x, window_size.y),
+    ];
+    let mut rects = DockRects {
+        areas: [None; 4],
+        groups: vec![],
+        free: Rect::new(l, t, window_size.x - r, window_size.y - b),
+    };
+    for edge in DockEdge::ALL {
+        if sizes[edge as usize] <= 0.0 {
+            continue;
+        }
+        let rect = area_rects[edge as usize];
+        rects.areas[edge as usize] = Some(rect);
+        let area = layout.area(edge);
+        let total: f32 = area.groups.iter().filter(|g| group_shown(g)).map(|g| g.weight).sum();
+        let mut start = 0.0;
+        for (i, group) in area.groups.iter().enumerate() {
+            if !group_shown(group) {
+                continue;
+            }
+            let end = start + group.weight / total;
+            let group_rect = if edge.vertical() {
+                Rect::new(
+                    rect.min.x, rect.min.y + start * rect.height(),
+                    rect.max.x, rect.min.y + end * rect.height(),
+                )
+            } else {
+                Rect::new(
+                    rect.min.x + start * rect.width(), rect.min.y,
+                    rect.min.x + end * rect.width(), rect.max.y,
+                )
+            };
+            rects.groups.push((edge, i, group_rect));
+            start = end;
+        }
+    }
+    rects
+}
+
+/// Which of the group's panels to show (the active one, unless it is minified)
+fn shown_panel(group: &DockGroup, is_shown: impl Fn(Entity) -> bool) -> Option<Entity> {
+    group.panels.get(group.active).copied()
+        .filter(|e| is_shown(*e))
+        .or_else(|| group.panels.iter().copied().find(|e| is_shown(*e)))
+}
+
+fn window_size(window: &Window) -> Vec2 {
+    Vec2::new(window.width(), window.height())
+}
+
+/// Cursor position in UI coordinates (origin at the top left)
+pub(super) fn cursor_ui(window: &Window) -> Option<Vec2> {
+    window.cursor_position()
+        .map(|pos| Vec2::new(pos.x, window.height() - pos.y))
+}
+
+fn apply_dock_layout(
+    mut layout: ResMut<DockLayout>,
+    mut rects: ResMut<DockRects>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    mut q_panel: Query<(Entity, &mut Style, &Visibility), With<PanelEntity>>,
+) {
+    // forget about panels that were closed
+    if layout.panels().any(|e| !q_panel.contains(e)) {
+        layout.retain(|e| q_panel.contains(e));
+    }
+    let Ok(window) = q_window.get_single() else {
+        return;
+    };
+    let is_shown = |e: Entity| q_panel.get(e).map(|(_, _, vis)| *vis != Visibility::Hidden).unwrap_or(false);
+    *rects = compute_rects(&layout, window_size(window), is_shown);
+
+    let mut placements = vec![];
+    for (edge, i, rect) in &rects.groups {
+        let group = &layout.area(*edge).groups[*i];
+        let active = shown_panel(group, is_shown);
+        let n_shown = group.panels.iter().filter(|e| is_shown(**e)).count();
+        let mut rect = Rect {
+            min: rect.min + SPLITTER_SIZE / 2.0,
+            max: rect.max - SPLITTER_SIZE / 2.0,
+        };
+        if n_shown > 1 {
+            rect.min.y += TAB_HEIGHT;
+        }
+        for e in &group.panels {
+            placements.push((*e, rect, Some(*e) == active));
+        }
+    }
+
+    for (e, rect, active) in placements {
+        let Ok((_, mut style, _)) = q_panel.get_mut(e) else {
+            continue;
+        };
+        let mut new = style.clone();
+        new.position = UiRect {
+            left: Val::Px(rect.min.x),
+            top: Val::Px(rect.min.y),
+            right: Val::Auto,
+            bottom: Val::Auto,
+        };
+        new.size = Size::new(Val::Px(rect.width().max(0.0)), Val::Px(rect.height().max(0.0)));
+        new.overflow = Overflow::Hidden;
+        new.display = if active { Display::Flex } else { Display::None };
+        // avoid triggering UI layout every frame
+        if *style != new {
+            *style = new;
+        }
+    }
+}
+
+fn update_editor_viewport(
+    rects: Res<DockRects>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    mut q_camera: Query<&mut Camera, With<EditorCamera>>,
+) {
+    let Ok(window) = q_window.get_single() else {
+        return;
+    };
+    let Ok(mut camera) = q_camera.get_single_mut() else {
+        return;
+    };
+    let viewport = if rects.areas.iter().all(|a| a.is_none()) {
+        None
+    } else {
+        let scale = window.scale_factor() as f32;
+        Some((
+            (rects.free.min * scale).max(Vec2::ZERO).as_uvec2(),
+            (rects.free.size() * scale).max(Vec2::ONE).as_uvec2(),
+        ))
+    };
+    let current = camera.viewport.as_ref().map(|v| (v.physical_position, v.physical_size));
+    if current != viewport {
+        camera.viewport = viewport.map(|(physical_position, physical_size)| Viewport {
+            physical_position,
+            physical_size,
+            ..Default::default()
+        });
+    }
+}
+
+fn reset_editor_viewport(
+    mut q_camera: Query<&mut Camera, With<EditorCamera>>,
+) {
+    for mut camera in &mut q_camera {
+        camera.viewport = None;
+    }
+}
+
+/// Highlights where a panel would be docked if dropped
+#[derive(Component)]
+struct DockPreview;
+
+fn setup_dock_preview(
+    mut commands: Commands,
+) {
+    commands.spawn((
+        NodeBundle {
+            focus_policy: FocusPolicy::Pass,
+            style: Style {
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+            visibility: Visibility::Hidden,
+            ..Default::default()
+        },
+        DockPreview,
+        ThemeBackground(|t| t.colors.dock_preview),
+        ThemeZIndex(|t| t.layers.dock_preview),
+        EditorCleanup,
+    ));
+}
+
+/// Figure out where a panel dropped at the cursor would go
+fn find_dock_target(
+    layout: &DockLayout,
+    rects: &DockRects,
+    window_size: Vec2,
+    cursor: Vec2,
+) -> Option<(DockTarget, Rect)> {
+    for (edge, i, rect) in &rects.groups {
+        if !rect.contains(cursor) {
+            continue;
+        }
+        let (t, len) = if edge.vertical() {
+            ((cursor.y - rect.min.y) / rect.height(), rect.height())
+        } else {
+            ((cursor.x - rect.min.x) / rect.width(), rect.width())
+        };
+        let zone = SPLIT_ZONE * len;
+        let part = |from: f32, to: f32| if edge.vertical() {
+            Rect::new(rect.min.x, rect.min.y + from, rect.max.x, rect.min.y + to)
+        } else {
+            Rect::new(rect.min.x + from, rect.min.y, rect.min.x + to, rect.max.y)
+        };
+        return Some(if t < SPLIT_ZONE {
+            (DockTarget::Split(*edge, *i, false), part(0.0, zone))
+        } else if t > 1.0 - SPLIT_ZONE {
+            (DockTarget::Split(*edge, *i, true), part(len - zone, len))
+        } else {
+            (DockTarget::Tab(*edge, *i), *rect)
+        });
+    }
+    let edge = if cursor.x < EDGE_SNAP {
+        DockEdge::Left
+    } else if cursor.x > window_size.x - EDGE_SNAP {
+        DockEdge::Right
+    } else if cursor.y < EDGE_SNAP {
+        DockEdge::Top
+    } else if cursor.y > window_size.y - EDGE_SNAP {
+        DockEdge::Bottom
+    } else {
+        return None;
+    };
+    let free = rects.free;
+    let size = layout.area(edge).size;
+    let rect = rects.areas[edge as usize].unwrap_or(match edge {
+        DockEdge::Left => Rect::new(free.min.x, free.min.y, free.min.x + size, free.max.y),
+        DockEdge::Right => Rect::new(free.max.x - size, free.min.y, free.max.x, free.max.y),
+        DockEdge::Top => Rect::new(free.min.x, free.min.y, free.max.x, free.min.y + size),
+        DockEdge::Bottom => Rect::new(free.min.x, free.max.y - size, free.max.x, free.max.y),
+    });
+    Some((DockTarget::Edge(edge), rect))
+}
+
+fn dock_panel_drag(
+    mouse: Res<Input<MouseButton>>,
+    mut motion: EventReader<MouseMotion>,
+    mut layout: ResMut<DockLayout>,
+    rects: Res<DockRects>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_titlebar: Query<(&Interaction, &PanelTitlebarEntity)>,
+    mut q_panel: Query<(&mut Style, &PanelEntity)>,
+    mut q_preview: Query<(&mut Style, &mut Visibility), (With<DockPreview>, Without<PanelEntity>)>,
+    mut dragging: Local<Option<(Entity, f32)>>,
+) {
+    // TODO: transition to a proper input mgmt framework like LWIM
+    let distance: f32 = motion.iter().map(|ev| ev.delta.length()).sum();
+    let Ok((mut preview_style, mut preview_vis)) = q_preview.get_single_mut() else {
+        return;
+    };
+    let Ok(window) = q_window.get_single() else {
+        return;
+    };
+    if dragging.is_none() && mouse.just_pressed(MouseButton::Left) {
+        *dragging = q_titlebar.iter()
+            .find(|(interaction, _)| **interaction == Interaction::Clicked)
+            .map(|(_, titlebar)| (titlebar.panel, 0.0));
+    }
+    let Some((panel, dragged)) = dragging.as_mut().map(|(panel, dragged)| {
+        *dragged += distance;
+        (*panel, *dragged)
+    }) else {
+        *preview_vis = Visibility::Hidden;
+        return;
+    };
+    let target = cursor_ui(window)
+        .and_then(|cursor| find_dock_target(&layout, &rects, window_size(window), cursor));
+
+    if !mouse.pressed(MouseButton::Left) {
+        *dragging = None;
+        *preview_vis = Visibility::Hidden;
+        if let (Some((target, _)), false) = (target, layout.is_docked(panel)) {
+            layout.dock(panel, target);
+        }
+        return;
+    }
+
+    if layout.is_docked(panel) {
+        if dragged < UNDOCK_DISTANCE {
+            return;
+        }
+        // keep it where it is, floating
+        layout.undock(panel);
+        if let Ok((mut style, panel)) = q_panel.get_mut(panel) {
+            style.size = panel.floating_size();
+            style.overflow = Overflow::Visible;
+            style.display = Display::Flex;
+        }
+    }
+
+    if let Some((_, rect)) = target {
+        preview_style.position = UiRect {
+            left: Val::Px(rect.min.x),
+            top: Val::Px(rect.min.y),
+            right: Val::Auto,
+            bottom: Val::Auto,
+        };
+        preview_style.size = Size::new(Val::Px(rect.width()), Val::Px(rect.height()));
+        *preview_vis = Visibility::Visible;
+    } else {
+        *preview_vis = Visibility::Hidden;
+    }
+}
+
+/// Draggable divider for resizing docks
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+enum DockSplitter {
+    /// At the inner edge of a dock area
+    Area(DockEdge),
+    /// Between two groups in a dock area
+    Groups {
+        edge: DockEdge,
+        a: usize,
+        b: usize,
+        /// Where group `a` starts and group `b` ends (along the area's length)
+        span: (f32, f32),
+    },
+}
+
+fn dock_splitter_drag(
+    mouse: Res<Input<MouseButton>>,
+    mut layout: ResMut<DockLayout>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_splitter: Query<(&Interaction, &DockSplitter)>,
+    mut dragging: Local<Option<DockSplitter>>,
+) {
+    // TODO: transition to a proper input mgmt framework like LWIM
+    if !mouse.pressed(MouseButton::Left) {
+        *dragging = None;
+        return;
+    }
+    if dragging.is_none() {
+        *dragging = q_splitter.iter()
+            .find(|(interaction, _)| **interaction == Interaction::Clicked)
+            .map(|(_, splitter)| *splitter);
+    }
+    let Some(splitter) = *dragging else {
+        return;
+    };
+    let Ok(window) = q_window.get_single() else {
+        return;
+    };
+    let Some(cursor) = cursor_ui(window) else {
+        return;
+    };
+    let size = window_size(window);
+    match splitter {
+        DockSplitter::Area(edge) => {
+            let new = match edge {
+                DockEdge::Left => cursor.x,
+                DockEdge::Right => size.x - cursor.x,
+                DockEdge::Top => cursor.y,
+                DockEdge::Bottom => size.y - cursor.y,
+            }.max(MIN_DOCK_SIZE);
+            if layout.area(edge).size != new {
+                layout.area_mut(edge).size = new;
+            }
+        }
+        DockSplitter::Groups { edge, a, b, span } => {
+            let pos = if edge.vertical() { cursor.y } else { cursor.x };
+            let t = ((pos - span.0) / (span.1 - span.0)).clamp(0.1, 0.9);
+            let area = layout.area(edge);
+            let (Some(ga), Some(gb)) = (area.groups.get(a), area.groups.get(b)) else {
+                return;
+            };
+            let total = ga.weight + gb.weight;
+            if (ga.weight - total * t).abs() > f32::EPSILON {
+                let area = layout.area_mut(edge);
+                area.groups[a].weight = total * t;
+                area.groups[b].weight = total * (1.0 - t);
+            }
+        }
+    }
+}
+
+/// Tab strips and splitters (respawned whenever they change)
+#[derive(Component)]
+struct DockDecoration;
+
+/// Switches the active panel in a group
+#[derive(Component)]
+struct DockTab {
+    edge: DockEdge,
+    group: usize,
+    index: usize,
+}
+
+/// What decorations to spawn; compared to know when to respawn them
+#[derive(PartialEq)]
+enum DecorationSpec {
+    Splitter(DockSplitter, Rect),
+    Tabs {
+        edge: DockEdge,
+        group: usize,
+        rect: Rect,
+        /// (index in group, title, is active)
+        tabs: Vec<(usize, String, bool)>,
+    },
+}
+
+fn update_dock_decorations(
+    mut commands: Commands,
+    assets: Res<EditorAssets>,
+    layout: Res<DockLayout>,
+    rects: Res<DockRects>,
+    q_panel: Query<(&PanelEntity, &Visibility)>,
+    q_decoration: Query<Entity, With<DockDecoration>>,
+    mut last: Local<Vec<DecorationSpec>>,
+) {
+    let is_shown = |e: Entity| q_panel.get(e).map(|(_, vis)| *vis != Visibility::Hidden).unwrap_or(false);
+    let mut specs = vec![];
+    for edge in DockEdge::ALL {
+        let Some(rect) = rects.areas[edge as usize] else {
+            continue;
+        };
+        let half = SPLITTER_SIZE / 2.0;
+        let splitter_rect = match edge {
+            DockEdge::Left => Rect::new(rect.max.x - half, rect.min.y, rect.max.x + half, rect.max.y),
+            DockEdge::Right => Rect::new(rect.min.x - half, rect.min.y, rect.min.x + half, rect.max.y),
+            DockEdge::Top => Rect::new(rect.min.x, rect.max.y - half, rect.max.x, rect.max.y + half),
+            DockEdge::Bottom => Rect::new(rect.min.x, rect.min.y - half, rect.max.x, rect.min.y + half),
+        };
+        specs.push(DecorationSpec::Splitter(DockSplitter::Area(edge), splitter_rect));
+
+        let groups: Vec<_> = rects.groups.iter()
+            .filter(|(e, _, _)| *e == edge)
+            .collect();
+        for pair in groups.windows(2) {
+            let (_, a, rect_a) = pair[0];
+            let (_, b, rect_b) = pair[1];
+            let (span, splitter_rect) = if edge.vertical() {
+                ((rect_a.min.y, rect_b.max.y), Rect::new(rect_a.min.x, rect_a.max.y - half, rect_a.max.x, rect_a.max.y + half))
+            } else {
+                ((rect_a.min.x, rect_b.max.x), Rect::new(rect_a.max.x - half, rect_a.min.y, rect_a.max.x + half, rect_a.max.y))
+            };
+            specs.push(DecorationSpec::Splitter(DockSplitter::Groups { edge, a: *a, b: *b, span }, splitter_rect));
+        }
+        for (_, i, rect) in groups {
+            let group = &layout.area(edge).groups[*i];
+            let active = shown_panel(group, is_shown);
+            let tabs: Vec<_> = group.panels.iter().enumerate()
+                .filter(|(_, e)| is_shown(**e))
+                .filter_map(|(index, e)| q_panel.get(*e).ok().map(|(panel, _)| (index, panel.title.clone(), Some(*e) == active)))
+                .collect();
+            if tabs.len() > 1 {
+                specs.push(DecorationSpec::Tabs {
+                    edge,
+                    group: *i,
+                    rect: Rect::new(rect.min.x + half, rect.min.y + half, rect.max.x - half, rect.min.y + half + TAB_HEIGHT),
+                    tabs,
+                });
+            }
+        }
+    }
+
+    // the decorations are despawned when exiting the editor, but `last` is kept
+    let despawned = q_decoration.is_empty() && !specs.is_empty();
+    if *last == specs && !despawned {
+        return;
+    }
+    for e in &q_decoration {
+        commands.entity(e).despawn_recursive();
+    }
+    let position = |rect: &Rect| UiRect {
+        left: Val::Px(rect.min.x),
+        top: Val::Px(rect.min.y),
+        right: Val::Auto,
+        bottom: Val::Auto,
+    };
+    for spec in &specs {
+        match spec {
+            DecorationSpec::Splitter(splitter, rect) => {
+                commands.spawn((
+                    NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            position: position(rect),
+                            size: Size::new(Val::Px(rect.width()), Val::Px(rect.height())),
+                            ..Default::default()
+                        },
+                        focus_policy: FocusPolicy::Block,
+                        ..Default::default()
+                    },
+                    Interaction::default(),
+                    *splitter,
+                    ThemeBackground(|t| t.colors.dock_splitter),
+                    ThemeZIndex(|t| t.layers.dock),
+                    DockDecoration,
+                    EditorCleanup,
+                ));
+            }
+            DecorationSpec::Tabs { edge, group, rect, tabs } => {
+                let strip = commands.spawn((
+                    NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            position: position(rect),
+                            size: Size::new(Val::Px(rect.width()), Val::Px(rect.height())),
+                            overflow: Overflow::Hidden,
+                            align_items: AlignItems::Stretch,
+                            ..Default::default()
+                        },
+                        focus_policy: FocusPolicy::Block,
+                        ..Default::default()
+                    },
+                    Interaction::default(),
+                    ThemeBackground(|t| t.colors.dock_tab_strip),
+                    ThemeZIndex(|t| t.layers.dock),
+                    DockDecoration,
+                    EditorCleanup,
+                )).id();
+                for (index, title, active) in tabs {
+                    let tab = commands.spawn((
+                        ButtonBundle {
+                            style: Style {
+                                margin: UiRect::right(Val::Px(1.0)),
+                                align_items: AlignItems::Center,
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        },
+                        DockTab {
+                            edge: *edge,
+                            group: *group,
+                            index: *index,
+                        },
+                        ThemeBackground(if *active {
+                            |t| t.colors.dock_tab_active
+                        } else {
+                            |t| t.colors.dock_tab_inactive
+                        }),
+                        ThemePadding(|t| UiRect::horizontal(Val::Px(t.spacing.dock_tab))),
+                        ClickBehavior::new().entity_system(dock_tab_handler),
+                    )).id();
+                    let text = commands.spawn((
+                        TextBundle {
+                            focus_policy: FocusPolicy::Pass,
+                            text: Text::from_section(title.as_str(), TextStyle {
+                                font: assets.font.clone(),
+                                ..Default::default()
+                            }),
+                            ..Default::default()
+                        },
+                        ThemeText::default(),
+                    )).id();
+                    commands.entity(tab).push_children(&[text]);
+                    commands.entity(strip).push_children(&[tab]);
+                }
+            }
+        }
+    }
+    *last = specs;
+}
+
+fn dock_tab_handler(
+    In(entity): In<Entity>,
+    q_tab: Query<&DockTab>,
+    mut layout: ResMut<DockLayout>,
+) {
+    let Ok(tab) = q_tab.get(entity) else { return; };
+    if let Some(group) = layout.area_mut(tab.edge).groups.get_mut(tab.group) {
+        group.active = tab.index.min(group.panels.len().saturating_sub(1));
+    }
+}
@@ -177,6 +177,7 @@ fn initialize_menu_actions(
 fn setup_global_menu(
     mut commands: Commands,
     assets: Res<EditorAssets>,
+    panels: Res<crate::ui::panel::PanelRegistry>,
 ) {
     let menu = spawn_menu(&mut commands, &*assets, UiRect {
         top: Val::Px(0.0),
@@ -187,6 +188,25 @@ fn setup_global_menu(
     let (_, app_submenu) = spawn_menuitem_submenu(&mut commands, &*assets, menu, "Iyes2D Editor");
     let (_, submenu2) = spawn_menuitem_submenu(&mut commands, &*assets, menu, "Menu 2");
     let (_, submenu3) = spawn_menuitem_submenu(&mut commands, &*assets, app_submenu, "Menu 3");
+    let (_, edit_submenu) = spawn_menuitem_submenu(&mut commands, &*assets, menu, "Edit");
+    spawn_menuitem_action(&mut commands, &*assets, edit_submenu, "Undo (Ctrl+Z)", crate::history::undo);
+    spawn_menuitem_action(&mut commands, &*assets, edit_submenu, "Redo (Ctrl+Y)", crate::history::redo);
+    spawn_menuitem_action(&mut commands, &*assets, edit_submenu, "Copy (Ctrl+C)", crate::entities::copy_selected);
+    spawn_menuitem_action(&mut commands, &*assets, edit_submenu, "Paste (Ctrl+V)", crate::entities::paste);
+    spawn_menuitem_action(&mut commands, &*assets, edit_submenu, "Duplicate (Ctrl+D)", crate::entities::duplicate_selected);
+    spawn_menuitem_action(&mut commands, &*assets, edit_submenu, "Delete (Del)", crate::entities::delete_selected);
+    spawn_menuitem_action(&mut commands, &*assets, edit_submenu, "Lock Selection", crate::lock::lock_selected);
+    spawn_menuitem_action(&mut commands, &*assets, edit_submenu, "Hide Selection", crate::lock::hide_selected);
+    spawn_menuitem_action(&mut commands, &*assets, edit_submenu, "Unlock All", crate::lock::unlock_all);
+    spawn_menuitem_action(&mut commands, &*assets, edit_submenu, "Unhide All", crate::lock::unhide_all);
+    let (_, arrange_submenu) = spawn_menuitem_submenu(&mut commands, &*assets, menu, "Arrange");
+    crate::arrange::spawn_arrange_menu(&mut commands, &*assets, arrange_submenu);
+    let (_, view_submenu) = spawn_menuitem_submenu(&mut commands, &*assets, menu, "View");
+    spawn_menuitem_action(&mut commands, &*assets, view_submenu, "Show/Hide Grid", crate::grid::toggle_grid);
+    let (_, camera_submenu) = spawn_menuitem_submenu(&mut commands, &*assets, menu, "Camera Bookmarks");
+    crate::camera::spawn_bookmarks_menu(&mut commands, &*assets, camera_submenu);
+    let (_, window_submenu) = spawn_menuitem_submenu(&mut commands, &*assets, menu, "Window");
+    crate::ui::panel::spawn_window_menu(&mut commands, &*assets, &*panels, window_submenu);
     spawn_menuitem_action(&mut commands, &*assets, app_submenu, "Test", test);
     spawn_menuitem_action(&mut commands, &*assets, submenu2, "Test 2", test);
     spawn_menuitem_action(&mut commands, &*assets, submenu3, "Test 3", test);
@@ -202,20 +222,20 @@ pub fn spawn_menu(
     let menu = commands.spawn((
         NodeBundle {
             focus_policy: FocusPolicy::Pass,
-            background_color: BackgroundColor(Color::WHITE),
-            z_index: ZIndex::Global(9010),
             style: Style {
                 position_type: PositionType::Absolute,
                 position,
                 flex_direction: FlexDirection::Column,
                 align_items: AlignItems::Stretch,
-                padding: UiRect::all(Val::Px(1.0)),
                 ..Default::default()
             },
             visibility,
             ..Default::default()
         },
         MenuContainer,
+        ThemeBackground(|t| t.colors.menu_background),
+        ThemePadding(|t| UiRect::all(Val::Px(t.spacing.menu))),
+        ThemeZIndex(|t| t.layers.menu),
         EditorCleanup,
     )).id();
     menu
@@ -262,12 +282,10 @@ fn spawn_menuitem_helper(
     let item = commands.spawn((
         ButtonBundle {
             focus_policy: FocusPolicy::Block,
-            background_color: BackgroundColor(Color::BLACK),
             style: Style {
                 flex_direction: FlexDirection::Row,
                 align_items: AlignItems::FlexStart,
                 justify_content: JustifyContent::SpaceBetween,
-                padding: UiRect::all(Val::Px(2.0)),
                 margin: UiRect::all(Val::Px(1.0)),
                 ..Default::default()
             },
@@ -276,6 +294,8 @@ fn spawn_menuitem_helper(
         MenuItem {
             parent_menu,
         },
+        ThemeBackground(|t| t.colors.menu_item),
+        ThemePadding(|t| UiRect::all(Val::Px(t.spacing.menu_item))),
         EditorCleanup,
     )).id();
     let text = commands.spawn((
@@ -290,12 +310,15 @@ fn spawn_menuitem_helper(
                 ..Default::default()
             },
             text: Text::from_section(text_str, TextStyle {
-                color: Color::WHITE,
                 font: assets.font.clone(),
-                font_size: 14.0,
+                ..Default::default()
             }),
             ..Default::default()
         },
+        ThemeText {
+            color: |t| t.colors.menu_text,
+            size: |t| t.font_sizes.menu,
+        },
     )).id();
     commands.entity(item).push_children(&[text]);
     if is_submenu {
@@ -317,12 +340,15 @@ fn spawn_menuitem_helper(
                     ..Default::default()
                 },
                 text: Text::from_section(">", TextStyle {
-                    color: Color::WHITE,
                     font: assets.font_bold.clone(),
-                    font_size: 16.0,
+                    ..Default::default()
                 }),
                 ..Default::default()
             },
+            ThemeText {
+                color: |t| t.colors.menu_text,
+                size: |t| t.font_sizes.title,
+            },
         )).id();
         commands.entity(submenu_indicator).push_children(&[submenu_indicator_text]);
         commands.entity(item).push_children(&[submenu_indicator]);
@@ -1,9 +1,23 @@
-use bevy::input::mouse::MouseMotion;
+use std::sync::Arc;
+use parking_lot::Mutex;
+
+use bevy::ecs::system::{BoxedSystem, SystemState};
+use bevy::input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel};
+use bevy::window::PrimaryWindow;
 
 use crate::crate_prelude::*;
+use crate::settings::{EditorSettings, SettingsCollectSet, SettingsRestoreSet};
 use crate::ui::tooltip::TooltipText;
 
 use super::SimpleButtVisual;
+use super::dock::{cursor_ui, DockLayout};
+
+/// Thickness of the draggable edges of panels, in pixels
+const RESIZE_HANDLE_SIZE: f32 = 4.0;
+/// How far one "line" of mouse wheel scrolling moves panel contents, in pixels
+const SCROLL_LINE_HEIGHT: f32 = 16.0;
+/// How far each registered panel is offset from the previous one, when first opened
+const PANEL_CASCADE_OFFSET: f32 = 24.0;
 
 pub struct PanelPlugin<S: States> {
     pub state: S,
@@ -11,11 +25,13 @@ pub struct PanelPlugin<S: States> {
 
 impl<S: States> Plugin for PanelPlugin<S> {
     fn build(&self, app: &mut App) {
+        app.init_resource::<PanelRegistry>();
+        app.add_editor_panel("Tool Options", populate_tool_options);
+        app.add_editor_panel("About Editor", populate_about);
         app.add_systems(
             (
                 setup_panel_layer,
                 setup_minibar,
-                spawn_panels,
             ).in_schedule(OnEnter(self.state.clone()))
         );
         app.add_systems(
@@ -29,8 +45,216 @@ impl<S: States> Plugin for PanelPlugin<S> {
                 panel_focus,
                 panel_titlebar_drag,
                 panel_titlebar_collapse,
+                panel_resize_drag,
+                panel_scroll,
+                update_window_menu,
             ).in_set(EditorSet)
         );
+        app.add_system(
+            spawn_registered_panels
+                .in_set(SettingsRestoreSet)
+        );
+        app.add_system(
+            restore_panel_settings
+                .in_set(SettingsRestoreSet)
+                .after(spawn_registered_panels)
+        );
+        app.add_system(
+            collect_panel_settings
+                .in_set(SettingsCollectSet)
+        );
+    }
+}
+
+/// Extension trait for adding panels to the editor
+pub trait EditorPanelAppExt {
+    /// Register a kind of panel with the editor
+    ///
+    /// `populate` is a system that gets the entity of the panel's content area
+    /// as input, and should spawn the panel's UI into it. It is run whenever
+    /// the panel is opened: upon entering the editor (unless the user closed
+    /// it last time) and from the "Window" menu.
+    ///
+    /// Panels are identified by their title, so it must be unique.
+    fn add_editor_panel<S, P>(&mut self, title: &str, populate: S) -> &mut Self
+        where S: IntoSystem<Entity, (), P>;
+}
+
+impl EditorPanelAppExt for App {
+    fn add_editor_panel<S, P>(&mut self, title: &str, populate: S) -> &mut Self
+        where S: IntoSystem<Entity, (), P>
+    {
+        self.init_resource::<PanelRegistry>();
+        self.world.resource_mut::<PanelRegistry>().register(title, populate);
+        self
+    }
+}
+
+/// The kinds of panels that the editor knows how to open
+///
+/// See `EditorPanelAppExt::add_editor_panel`.
+#[derive(Resource, Default)]
+pub struct PanelRegistry {
+    kinds: Vec<PanelKind>,
+}
+
+struct PanelKind {
+    title: String,
+    populate: Arc<Mutex<PanelPopulate>>,
+}
+
+struct PanelPopulate {
+    system: BoxedSystem<Entity, ()>,
+    initialized: bool,
+}
+
+impl PanelRegistry {
+    pub fn register<S, P>(&mut self, title: &str, populate: S)
+        where S: IntoSystem<Entity, (), P>
+    {
+        if self.kinds.iter().any(|kind| kind.title == title) {
+            warn!("Editor panel {:?} is already registered.", title);
+            return;
+        }
+        self.kinds.push(PanelKind {
+            title: title.into(),
+            populate: Arc::new(Mutex::new(PanelPopulate {
+                system: Box::new(IntoSystem::into_system(populate)),
+                initialized: false,
+            })),
+        });
+    }
+
+    /// The titles of all registered panels, in registration order
+    pub fn titles(&self) -> impl Iterator<Item = &str> {
+        self.kinds.iter().map(|kind| kind.title.as_str())
+    }
+}
+
+/// Spawn a registered panel and run its populate system
+pub(crate) fn open_panel(world: &mut World, title: &str) {
+    let found = world.resource::<PanelRegistry>().kinds.iter()
+        .enumerate()
+        .find(|(_, kind)| kind.title == title)
+        .map(|(index, kind)| (index, kind.populate.clone()));
+    let Some((index, populate)) = found else {
+        error!("Editor panel {:?} is not registered.", title);
+        return;
+    };
+    let mut state: SystemState<(Commands, Res<EditorAssets>)> = SystemState::new(world);
+    let (mut commands, assets) = state.get_mut(world);
+    let e_contents = spawn_panel(&mut commands, &*assets, title);
+    state.apply(world);
+
+    // cascade the default positions, so that the panels don't all overlap
+    // (the saved position, if any, is applied later)
+    let offset = PANEL_CASCADE_OFFSET * index as f32;
+    if let Some(panel) = world.get::<PanelContentsEntity>(e_contents).map(|c| c.panel) {
+        if let Some(mut style) = world.get_mut::<Style>(panel) {
+            style.position.top = Val::Px(offset);
+            style.position.right = Val::Px(64.0 + offset);
+        }
+    }
+
+    let mut populate = populate.lock();
+    if !populate.initialized {
+        populate.system.initialize(world);
+        populate.initialized = true;
+    }
+    populate.system.run(e_contents, world);
+    populate.system.apply_buffers(world);
+}
+
+/// Despawn a panel, along with its minibar button (if minified)
+pub(crate) fn close_panel(world: &mut World, panel: Entity) {
+    let mut q_minibar = world.query::<(Entity, &MinibarButt)>();
+    let buttons: Vec<_> = q_minibar.iter(world)
+        .filter(|(_, butt)| butt.panel == panel)
+        .map(|(e, _)| e)
+        .collect();
+    for e in buttons.into_iter().chain(std::iter::once(panel)) {
+        if let Some(e) = world.get_entity_mut(e) {
+            e.despawn_recursive();
+        }
+    }
+}
+
+/// Open the registered panels, except for those that were closed last time
+pub(super) fn spawn_registered_panels(world: &mut World) {
+    let settings = world.resource::<EditorSettings>();
+    let titles: Vec<String> = world.resource::<PanelRegistry>().titles()
+        .filter(|title| !settings.panels.get(*title).map(|saved| saved.closed).unwrap_or(false))
+        .map(|title| title.to_owned())
+        .collect();
+    for title in titles {
+        open_panel(world, &title);
+    }
+}
+
+/// Menu item for opening/closing a panel
+#[derive(Component)]
+struct WindowMenuItem {
+    title: String,
+}
+
+/// Populate the "Window" menu with all registered panels
+pub(crate) fn spawn_window_menu(
+    commands: &mut Commands,
+    assets: &EditorAssets,
+    registry: &PanelRegistry,
+    submenu: Entity,
+) {
+    for title in registry.titles() {
+        let owned = title.to_owned();
+        let item = crate::ui::menu::spawn_menuitem_action(
+            commands, assets, submenu, &window_menu_label(title, false),
+            move |world: &mut World| toggle_panel(world, &owned),
+        );
+        commands.entity(item).insert(WindowMenuItem {
+            title: title.into(),
+        });
+    }
+}
+
+fn window_menu_label(title: &str, open: bool) -> String {
+    format!("[{}] {}", if open { "x" } else { " " }, title)
+}
+
+/// Close the panel if it is open, otherwise open it
+fn toggle_panel(world: &mut World, title: &str) {
+    let mut q_panel = world.query::<(Entity, &PanelEntity)>();
+    let open: Vec<_> = q_panel.iter(world)
+        .filter(|(_, panel)| panel.title == title)
+        .map(|(e, _)| e)
+        .collect();
+    if open.is_empty() {
+        open_panel(world, title);
+    } else {
+        for e in open {
+            close_panel(world, e);
+        }
+    }
+}
+
+/// Keep the checkmarks in the "Window" menu up to date
+fn update_window_menu(
+    q_panel: Query<&PanelEntity>,
+    q_item: Query<(&WindowMenuItem, &Children)>,
+    mut q_text: Query<&mut Text>,
+) {
+    for (item, children) in &q_item {
+        let open = q_panel.iter().any(|panel| panel.title == item.title);
+        let label = window_menu_label(&item.title, open);
+        for child in children.iter() {
+            let Ok(mut text) = q_text.get_mut(*child) else {
+                continue;
+            };
+            // avoid triggering text layout every frame
+            if text.sections[0].value != label {
+                text.sections[0].value = label;
+            }
+            break;
+        }
     }
 }
 
@@ -41,9 +265,26 @@ struct PanelLayerEntity(Entity);
 
 /// Marker for panels
 #[derive(Component)]
-struct PanelEntity {
+pub(super) struct PanelEntity {
     titlebar: Entity,
+    viewport: Entity,
     contents: Entity,
+    pub(super) title: String,
+    /// Size set by resizing; `None` to fit the contents
+    size: Option<Vec2>,
+    /// Collapsed into the titlebar
+    collapsed: bool,
+}
+
+impl PanelEntity {
+    /// What the panel's size should be, when not docked
+    pub(super) fn floating_size(&self) -> Size {
+        match self.size {
+            Some(size) if self.collapsed => Size::new(Val::Px(size.x), Val::Auto),
+            Some(size) => Size::new(Val::Px(size.x), Val::Px(size.y)),
+            None => Size::new(Val::Auto, Val::Auto),
+        }
+    }
 }
 
 /// Marker for panels' content areas
@@ -52,11 +293,48 @@ struct PanelContentsEntity {
     panel: Entity,
 }
 
-/// Marker for panels' titlebars
+/// The part of a panel that shows (and clips) its content area
+///
+/// The content area is scrolled within it.
 #[derive(Component)]
-struct PanelTitlebarEntity {
+struct PanelViewport {
     panel: Entity,
     contents: Entity,
+    /// How far the contents are scrolled down, in pixels
+    scroll: f32,
+}
+
+/// Marker for panels' titlebars
+#[derive(Component)]
+pub(super) struct PanelTitlebarEntity {
+    pub(super) panel: Entity,
+}
+
+/// Draggable edge or corner for resizing a panel
+#[derive(Component)]
+struct PanelResizeHandle {
+    panel: Entity,
+    /// Which edges move: -1 for left/top, 1 for right/bottom, 0 for neither
+    dir: Vec2,
+}
+
+/// Limits for resizing a panel
+///
+/// Insert this on a panel's content area (the entity returned by `spawn_panel`,
+/// or given to the populate system of a registered panel).
+#[derive(Component, Debug, Clone, Copy)]
+pub struct PanelSizeLimits {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl Default for PanelSizeLimits {
+    fn default() -> Self {
+        PanelSizeLimits {
+            min: Vec2::new(96.0, 48.0),
+            max: Vec2::splat(f32::INFINITY),
+        }
+    }
 }
 
 #[derive(Component)]
@@ -74,9 +352,9 @@ fn setup_panel_layer(
                 position: UiRect::all(Val::Px(0.0)),
                 ..Default::default()
             },
-            z_index: ZIndex::Global(9001), // TODO: make this configurable
             ..Default::default()
         },
+        ThemeZIndex(|t| t.layers.panels),
         EditorCleanup,
     )).id();
     commands.insert_resource(PanelLayerEntity(layer));
@@ -103,42 +381,51 @@ fn reparent_panels(world: &mut World) {
     }
 }
 
-fn spawn_panels(
+fn populate_tool_options(
+    In(e_contents): In<Entity>,
     mut commands: Commands,
     assets: Res<EditorAssets>,
+    snap_settings: Res<crate::transform::SnapSettings>,
 ) {
-    let e_contents = spawn_panel(&mut commands, &*assets, "Tool Options");
     let label_snap = commands.spawn((
         TextBundle {
-            text: Text::from_section("Snap to Grid:", TextStyle {
+            text: Text::from_section("Snap (hold Alt to invert):", TextStyle {
                 font: assets.font.clone(),
-                font_size: 12.0,
-                color: Color::BLACK,
+                ..Default::default()
             }),
             ..Default::default()
         },
+        ThemeText::default(),
     )).id();
     let label_us = commands.spawn((
         TextBundle {
             text: Text::from_section("Uniform Scaling is currently the preferred mode of operation.", TextStyle {
                 font: assets.font.clone(),
-                font_size: 12.0,
-                color: Color::BLACK,
+                ..Default::default()
             }),
             ..Default::default()
         },
+        ThemeText::default(),
     )).id();
-    commands.entity(e_contents).push_children(&[label_snap, label_us]);
-    let e_contents = spawn_panel(&mut commands, &*assets, "About Editor");
+    commands.entity(e_contents).push_children(&[label_snap]);
+    crate::transform::spawn_snap_options(&mut commands, &*assets, &*snap_settings, e_contents);
+    commands.entity(e_contents).push_children(&[label_us]);
+}
+
+fn populate_about(
+    In(e_contents): In<Entity>,
+    mut commands: Commands,
+    assets: Res<EditorAssets>,
+) {
     let label_ver = commands.spawn((
         TextBundle {
             text: Text::from_section(format!("Editor Version: {}", env!("CARGO_PKG_VERSION")), TextStyle {
                 font: assets.font.clone(),
-                font_size: 12.0,
-                color: Color::BLACK,
+                ..Default::default()
             }),
             ..Default::default()
         },
+        ThemeText::default(),
     )).id();
     commands.entity(e_contents).push_children(&[label_ver]);
 }
@@ -180,8 +467,10 @@ fn panel_focus(
 
 fn panel_titlebar_collapse(
     time: Res<Time>,
+    dock: Res<DockLayout>,
     mut q_titlebar: Query<(&Interaction, &PanelTitlebarEntity, &mut PanelTitlebarDoubleclick)>,
-    mut q_contents: Query<&mut Style, With<PanelContentsEntity>>,
+    mut q_panel: Query<(&mut PanelEntity, &mut Style)>,
+    mut q_viewport: Query<&mut Style, (With<PanelViewport>, Without<PanelEntity>)>,
 ) {
     for (interaction, titlebar, mut dblclick) in &mut q_titlebar {
         if *interaction == Interaction::Clicked {
@@ -189,11 +478,17 @@ fn panel_titlebar_collapse(
             let progress = dblclick.timer.percent();
             if progress > 0.0 && progress < 1.0 {
                 // started but not finished, successful doubleclick
-                let mut style = q_contents.get_mut(titlebar.contents).unwrap();
-                if style.display == Display::None {
-                    style.display = Display::Flex;
+                let (mut panel, mut p_style) = q_panel.get_mut(titlebar.panel).unwrap();
+                panel.collapsed = !panel.collapsed;
+                let mut style = q_viewport.get_mut(panel.viewport).unwrap();
+                style.display = if panel.collapsed {
+                    Display::None
                 } else {
-                    style.display = Display::None;
+                    Display::Flex
+                };
+                // a resized panel should still shrink to its titlebar
+                if !dock.is_docked(titlebar.panel) {
+                    p_style.size = panel.floating_size();
                 }
             }
             // start new timer
@@ -205,7 +500,7 @@ fn panel_titlebar_collapse(
     }
 }
 
-fn panel_titlebar_drag(
+pub(super) fn panel_titlebar_drag(
     mut mousemotion: EventReader<MouseMotion>,
     q_titlebar: Query<(&Interaction, &PanelTitlebarEntity)>,
     mut q_panel: Query<&mut Style, With<PanelEntity>>,
@@ -225,8 +520,104 @@ fn panel_titlebar_drag(
     }
 }
 
+fn panel_resize_drag(
+    mut mousemotion: EventReader<MouseMotion>,
+    dock: Res<DockLayout>,
+    q_handle: Query<(&Interaction, &PanelResizeHandle)>,
+    mut q_panel: Query<(&mut PanelEntity, &mut Style, &Node, &GlobalTransform)>,
+    q_limits: Query<&PanelSizeLimits>,
+) {
+    let delta: Vec2 = mousemotion.iter().map(|ev| ev.delta).sum();
+    let Some(handle) = q_handle.iter()
+        .find(|(interaction, _)| **interaction == Interaction::Clicked)
+        .map(|(_, handle)| handle)
+    else {
+        return;
+    };
+    // docked panels are resized using the dock splitters
+    if delta == Vec2::ZERO || dock.is_docked(handle.panel) {
+        return;
+    }
+    let Ok((mut panel, mut style, node, transform)) = q_panel.get_mut(handle.panel) else {
+        return;
+    };
+    let limits = q_limits.get(panel.contents).copied().unwrap_or_default();
+    let old_size = panel.size.unwrap_or(node.size());
+    let new_size = (old_size + delta * handle.dir).max(limits.min).min(limits.max);
+    // moving the left/top edge also moves the panel
+    let mut pos = match (style.position.left, style.position.top) {
+        (Val::Px(x), Val::Px(y)) => Vec2::new(x, y),
+        _ => transform.translation().truncate() - node.size() / 2.0,
+    };
+    if handle.dir.x < 0.0 {
+        pos.x += old_size.x - new_size.x;
+    }
+    if handle.dir.y < 0.0 {
+        pos.y += old_size.y - new_size.y;
+    }
+    style.position = UiRect {
+        left: Val::Px(pos.x),
+        top: Val::Px(pos.y),
+        right: Val::Auto,
+        bottom: Val::Auto,
+    };
+    panel.size = Some(new_size);
+    style.size = panel.floating_size();
+}
+
+fn panel_scroll(
+    mut evr_wheel: EventReader<MouseWheel>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_panel: Query<&ZIndex, With<PanelEntity>>,
+    mut q_viewport: Query<(&mut PanelViewport, &Node, &GlobalTransform, &ComputedVisibility)>,
+    mut q_contents: Query<(&Node, &mut Style), With<PanelContentsEntity>>,
+) {
+    let delta: f32 = evr_wheel.iter()
+        .map(|ev| match ev.unit {
+            MouseScrollUnit::Line => ev.y * SCROLL_LINE_HEIGHT,
+            MouseScrollUnit::Pixel => ev.y,
+        })
+        .sum();
+    let cursor = q_window.get_single().ok().and_then(cursor_ui);
+    // scroll the topmost panel under the cursor
+    let hovered = cursor.filter(|_| delta != 0.0).and_then(|cursor| {
+        q_viewport.iter()
+            .filter(|(_, node, transform, visibility)| {
+                visibility.is_visible() &&
+                Rect::from_center_size(transform.translation().truncate(), node.size()).contains(cursor)
+            })
+            .max_by_key(|(viewport, ..)| match q_panel.get(viewport.panel) {
+                Ok(ZIndex::Local(z)) => *z,
+                _ => 0,
+            })
+            .map(|(viewport, ..)| viewport.panel)
+    });
+    for (mut viewport, node, _, _) in &mut q_viewport {
+        let Ok((contents_node, mut style)) = q_contents.get_mut(viewport.contents) else {
+            continue;
+        };
+        // also keep the scroll in range when the contents or panel change size
+        let max_scroll = (contents_node.size().y - node.size().y).max(0.0);
+        let mut scroll = viewport.scroll;
+        if hovered == Some(viewport.panel) {
+            scroll -= delta;
+        }
+        let scroll = scroll.clamp(0.0, max_scroll);
+        if scroll != viewport.scroll {
+            viewport.scroll = scroll;
+        }
+        let top = Val::Px(-scroll);
+        if style.position.top != top {
+            style.position.top = top;
+        }
+    }
+}
+
 /// Helper function to create a Panel
 ///
+/// Prefer registering panels with `EditorPanelAppExt::add_editor_panel`,
+/// so that they can be reopened from the "Window" menu after being closed.
+///
 /// Returns tuple of the entity ids of the panel itself and its content area
 /// The content area is where the caller
 /// can populate the panel with UI elements.
@@ -239,13 +630,13 @@ pub fn spawn_panel(
 ) -> Entity {
     let container = commands.spawn(()).id();
     let titlebar = commands.spawn(()).id();
+    let viewport = commands.spawn(()).id();
     let contents = commands.spawn(()).id();
     commands.entity(container).insert((
         NodeBundle {
-            // focus_policy: FocusPolicy::Block,
-            background_color: BackgroundColor(Color::WHITE),
+            // clicks on the panel body must not go through to the world
+            focus_policy: FocusPolicy::Block,
             style: Style {
-                padding: UiRect::all(Val::Px(2.0)),
                 position_type: PositionType::Absolute,
                 position: UiRect {
                     top: Val::Px(0.0),
@@ -260,18 +651,22 @@ pub fn spawn_panel(
             z_index: ZIndex::Local(0),
             ..Default::default()
         },
+        Interaction::default(),
         PanelEntity {
-            titlebar, contents,
+            titlebar, viewport, contents,
+            title: title_str.into(),
+            size: None,
+            collapsed: false,
         },
+        ThemeBackground(|t| t.colors.panel_border),
+        ThemePadding(|t| UiRect::all(Val::Px(t.spacing.panel_border))),
         EditorCleanup,
     ));
     commands.entity(titlebar).insert((
         NodeBundle {
             // focus_policy: FocusPolicy::Block,
-            background_color: BackgroundColor(Color::BLACK),
             style: Style {
                 flex_grow: 0.0,
-                padding: UiRect::all(Val::Px(2.0)),
                 align_items: AlignItems::Center,
                 justify_content: JustifyContent::SpaceBetween,
                 ..Default::default()
@@ -284,16 +679,38 @@ pub fn spawn_panel(
         },
         PanelTitlebarEntity {
             panel: container,
+        },
+        ThemeBackground(|t| t.colors.panel_titlebar),
+        ThemePadding(|t| UiRect::all(Val::Px(t.spacing.panel_titlebar))),
+    ));
+    commands.entity(viewport).insert((
+        NodeBundle {
+            focus_policy: FocusPolicy::Pass,
+            style: Style {
+                flex_grow: 1.0,
+                flex_shrink: 1.0,
+                // allow shrinking smaller than the contents
+                min_size: Size::new(Val::Px(0.0), Val::Px(0.0)),
+                overflow: Overflow::Hidden,
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Stretch,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        PanelViewport {
+            panel: container,
             contents,
+            scroll: 0.0,
         },
+        ThemeBackground(|t| t.colors.panel_background),
     ));
     commands.entity(contents).insert((
         NodeBundle {
             focus_policy: FocusPolicy::Pass,
-            background_color: BackgroundColor(Color::rgb(0.75, 0.75, 0.75)),
             style: Style {
                 flex_grow: 1.0,
-                padding: UiRect::all(Val::Px(4.0)),
+                flex_shrink: 0.0,
                 align_items: AlignItems::FlexStart,
                 flex_direction: FlexDirection::Column,
                 ..Default::default()
@@ -302,9 +719,10 @@ pub fn spawn_panel(
         },
         PanelContentsEntity {
             panel: container,
-        }
+        },
+        ThemePadding(|t| UiRect::all(Val::Px(t.spacing.panel_contents))),
     ));
-    let title = commands.spawn(
+    let title = commands.spawn((
         TextBundle {
             style: Style {
                 margin: UiRect {
@@ -317,12 +735,15 @@ pub fn spawn_panel(
             },
             text: Text::from_section(title_str, TextStyle {
                 font: assets.font_bold.clone(),
-                font_size: 16.0,
-                color: Color::WHITE,
+                ..Default::default()
             }),
             ..Default::default()
-        }
-    ).id();
+        },
+        ThemeText {
+            color: |t| t.colors.panel_title,
+            size: |t| t.font_sizes.title,
+        },
+    )).id();
     let mini_butt = commands.spawn((
         ButtonBundle {
             style: Style {
@@ -385,11 +806,53 @@ pub fn spawn_panel(
     commands.entity(close_butt).push_children(&[close_icon]);
     commands.entity(mini_butt).push_children(&[mini_icon]);
     commands.entity(titlebar).push_children(&[mini_butt, title, close_butt]);
-    commands.entity(container).push_children(&[titlebar, contents]);
+    commands.entity(viewport).push_children(&[contents]);
+    commands.entity(container).push_children(&[titlebar, viewport]);
+    spawn_resize_handles(commands, container);
 
     contents
 }
 
+/// Spawn the draggable edges and corners of a panel
+fn spawn_resize_handles(
+    commands: &mut Commands,
+    panel: Entity,
+) {
+    // (start, end, size) along one axis
+    let span = |dir: f32| match dir {
+        d if d < 0.0 => (Val::Px(0.0), Val::Auto, Val::Px(RESIZE_HANDLE_SIZE)),
+        d if d > 0.0 => (Val::Auto, Val::Px(0.0), Val::Px(RESIZE_HANDLE_SIZE)),
+        _ => (Val::Px(RESIZE_HANDLE_SIZE), Val::Px(RESIZE_HANDLE_SIZE), Val::Auto),
+    };
+    // edges first, so the corners are on top
+    let dirs = [
+        Vec2::new(-1.0, 0.0), Vec2::new(1.0, 0.0), Vec2::new(0.0, -1.0), Vec2::new(0.0, 1.0),
+        Vec2::new(-1.0, -1.0), Vec2::new(1.0, -1.0), Vec2::new(-1.0, 1.0), Vec2::new(1.0, 1.0),
+    ];
+    for dir in dirs {
+        let (left, right, width) = span(dir.x);
+        let (top, bottom, height) = span(dir.y);
+        let handle = commands.spawn((
+            NodeBundle {
+                focus_policy: FocusPolicy::Block,
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect { left, right, top, bottom },
+                    size: Size::new(width, height),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Interaction::default(),
+            PanelResizeHandle {
+                panel,
+                dir,
+            },
+        )).id();
+        commands.entity(panel).push_children(&[handle]);
+    }
+}
+
 #[derive(Component)]
 struct MinibarTop;
 
@@ -416,7 +879,6 @@ fn setup_minibar(
     let _minibar = commands.spawn((
         NodeBundle {
             focus_policy: FocusPolicy::Pass,
-            z_index: ZIndex::Global(9010), // TODO: make this configurable
             style: Style {
                 position_type: PositionType::Absolute,
                 position: UiRect {
@@ -432,10 +894,11 @@ fn setup_minibar(
                 flex_wrap: FlexWrap::Wrap,
                 ..Default::default()
             },
-            background_color: BackgroundColor(Color::rgb(0.75, 0.75, 0.75)),
             ..Default::default()
         },
         MinibarTop,
+        ThemeBackground(|t| t.colors.bar_background),
+        ThemeZIndex(|t| t.layers.minibar),
         EditorCleanup,
     )).id();
 }
@@ -453,52 +916,65 @@ fn mini_butt_handler(
     *q_panel.get_mut(butt.panel).unwrap() = Visibility::Hidden;
     // create a minibar button for restoring it
     for e_minibar in &q_minibar {
-        let button = commands.spawn((
-            ButtonBundle {
-                style: Style {
-                    align_items: AlignItems::Center,
-                    align_content: AlignContent::Center,
-                    justify_content: JustifyContent::Center,
-                    size: Size::new(Val::Px(64.0), Val::Px(64.0)),
-                    ..Default::default()
-                },
-                image: UiImage::new(assets.image_ui_toolbar_depressed.clone()),
+        spawn_minibar_butt(&mut commands, &*assets, e_minibar, butt.panel, &butt.title);
+    }
+}
+
+fn spawn_minibar_butt(
+    commands: &mut Commands,
+    assets: &EditorAssets,
+    e_minibar: Entity,
+    panel: Entity,
+    title: &str,
+) {
+    let button = commands.spawn((
+        ButtonBundle {
+            style: Style {
+                align_items: AlignItems::Center,
+                align_content: AlignContent::Center,
+                justify_content: JustifyContent::Center,
+                size: Size::new(Val::Px(64.0), Val::Px(64.0)),
                 ..Default::default()
             },
-            MinibarButt {
-                panel: butt.panel,
-            },
-            ClickBehavior::new().entity_system(minibar_butt_handler),
-            SimpleButtVisual,
-            TooltipText {
-                title: butt.title.clone(),
-                text: "Click to re-open the Panel.".into(),
-            },
-        )).id();
-        // construct the string using initials from the title
-        let mut minitext_str = String::new();
-        minitext_str.push('[');
-        for word in butt.title.split_whitespace() {
-            if !word.is_empty() {
-                for c in word.chars().next().unwrap().to_uppercase() {
-                    minitext_str.push(c);
-                }
+            image: UiImage::new(assets.image_ui_toolbar_depressed.clone()),
+            ..Default::default()
+        },
+        MinibarButt {
+            panel,
+        },
+        ClickBehavior::new().entity_system(minibar_butt_handler),
+        SimpleButtVisual,
+        TooltipText {
+            title: title.into(),
+            text: "Click to re-open the Panel.".into(),
+        },
+    )).id();
+    // construct the string using initials from the title
+    let mut minitext_str = String::new();
+    minitext_str.push('[');
+    for word in title.split_whitespace() {
+        if !word.is_empty() {
+            for c in word.chars().next().unwrap().to_uppercase() {
+                minitext_str.push(c);
             }
         }
-        minitext_str.push(']');
-        let minitext = commands.spawn((
-            TextBundle {
-                text: Text::from_section(minitext_str, TextStyle {
-                    font: assets.font_bold.clone(),
-                    font_size: 16.0,
-                    color: Color::BLACK,
-                }),
-                ..Default::default()
-            },
-        )).id();
-        commands.entity(button).push_children(&[minitext]);
-        commands.entity(e_minibar).push_children(&[button]);
     }
+    minitext_str.push(']');
+    let minitext = commands.spawn((
+        TextBundle {
+            text: Text::from_section(minitext_str, TextStyle {
+                font: assets.font_bold.clone(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        ThemeText {
+            color: |t| t.colors.text,
+            size: |t| t.font_sizes.title,
+        },
+    )).id();
+    commands.entity(button).push_children(&[minitext]);
+    commands.entity(e_minibar).push_children(&[button]);
 }
 
 fn minibar_butt_handler(
@@ -525,5 +1001,79 @@ fn close_butt_handler(
     mut commands: Commands,
 ) {
     let Ok(butt) = q_butt.get(entity) else { return; };
-    commands.entity(butt.panel).despawn_recursive();
+    let panel = butt.panel;
+    commands.add(move |world: &mut World| close_panel(world, panel));
+}
+
+/// Apply the saved panel state to the freshly spawned panels
+fn restore_panel_settings(
+    mut commands: Commands,
+    assets: Res<EditorAssets>,
+    settings: Res<EditorSettings>,
+    mut q_panel: Query<(Entity, &mut PanelEntity, &mut Style, &mut Visibility)>,
+    mut q_viewport: Query<&mut Style, (With<PanelViewport>, Without<PanelEntity>)>,
+    q_minibar: Query<Entity, With<MinibarTop>>,
+) {
+    for (e, mut panel, mut style, mut visibility) in &mut q_panel {
+        let Some(saved) = settings.panels.get(&panel.title) else {
+            continue;
+        };
+        if saved.closed {
+            commands.entity(e).despawn_recursive();
+            continue;
+        }
+        if let Some(pos) = saved.position {
+            style.position = UiRect {
+                left: Val::Px(pos.x),
+                top: Val::Px(pos.y),
+                right: Val::Auto,
+                bottom: Val::Auto,
+            };
+        }
+        if saved.collapsed {
+            panel.collapsed = true;
+            if let Ok(mut style) = q_viewport.get_mut(panel.viewport) {
+                style.display = Display::None;
+            }
+        }
+        if let Some(size) = saved.size {
+            panel.size = Some(size);
+        }
+        style.size = panel.floating_size();
+        if saved.minified {
+            *visibility = Visibility::Hidden;
+            for e_minibar in &q_minibar {
+                spawn_minibar_butt(&mut commands, &*assets, e_minibar, e, &panel.title);
+            }
+        }
+    }
+}
+
+/// Keep the saved panel state up to date
+///
+/// Panels we know about that no longer exist have been closed.
+fn collect_panel_settings(
+    mut settings: ResMut<EditorSettings>,
+    dock: Res<DockLayout>,
+    q_panel: Query<(Entity, &PanelEntity, &Node, &GlobalTransform, &Visibility)>,
+) {
+    let mut panels = settings.panels.clone();
+    for saved in panels.values_mut() {
+        saved.closed = true;
+    }
+    for (e, panel, node, transform, visibility) in &q_panel {
+        let saved = panels.entry(panel.title.clone()).or_default();
+        saved.closed = false;
+        saved.minified = *visibility == Visibility::Hidden;
+        saved.collapsed = panel.collapsed;
+        saved.size = panel.size;
+        // docked panels keep their last floating position
+        if !dock.is_docked(e) {
+            saved.position = Some(transform.translation().truncate() - node.size() / 2.0);
+        }
+    }
+    // avoid triggering change detection (and saving) every frame
+    if panels != settings.panels {
+        settings.panels = panels;
+    }
 }
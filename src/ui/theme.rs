@@ -0,0 +1,343 @@
+//! Colors, font sizes, paddings and z-indices of the editor UI
+//!
+//! The theme is a RON asset (`*.theme.ron`), loaded together with the rest of
+//! the editor's assets, from the `iyes2d_editor.theme` key. Edit the file (or
+//! point the key at another one) to restyle the editor. If Bevy's asset hot
+//! reloading is enabled, changes are applied while the editor is running.
+//!
+//! UI nodes get their themed values from marker components (`ThemeBackground`,
+//! `ThemeText`, `ThemePadding`, `ThemeZIndex`), that say which value of the
+//! theme to use. Add them to your own UI to make it follow the theme.
+
+use bevy::asset::{AssetLoader, LoadContext, LoadedAsset};
+use bevy::reflect::TypeUuid;
+use bevy::ui::UiSystem;
+use bevy::utils::BoxedFuture;
+use serde::{Deserialize, Serialize};
+
+use crate::crate_prelude::*;
+
+pub(crate) struct ThemePlugin<S: States> {
+    pub state: S,
+}
+
+impl<S: States> Plugin for ThemePlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<EditorTheme>();
+        app.init_asset_loader::<EditorThemeLoader>();
+        app.add_systems(
+            (
+                init_theme,
+            ).in_schedule(OnEnter(self.state.clone()))
+        );
+        app.add_systems(
+            (
+                remove_resource::<EditorTheme>,
+            ).in_schedule(OnExit(self.state.clone()))
+        );
+        app.add_system(
+            theme_hot_reload
+                .in_set(EditorSet)
+        );
+        app.add_system(
+            apply_theme
+                .in_set(EditorSet)
+                .in_base_set(CoreSet::PostUpdate)
+                .before(UiSystem::Flex)
+                .run_if(resource_exists::<EditorTheme>())
+        );
+    }
+}
+
+/// The look of the editor UI
+///
+/// While in the editor, the currently used theme is also available as a resource.
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize, TypeUuid)]
+#[uuid = "6a1f3c52-93b4-4e0d-8d57-2f0c1b9e7a41"]
+#[serde(default)]
+pub struct EditorTheme {
+    pub colors: ThemeColors,
+    pub font_sizes: ThemeFontSizes,
+    pub spacing: ThemeSpacing,
+    pub layers: ThemeLayers,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeColors {
+    /// Text inside panels and other UI
+    pub text: Color,
+    /// Text that is being edited or otherwise stands out
+    pub text_highlight: Color,
+    /// Frame around panels
+    pub panel_border: Color,
+    pub panel_titlebar: Color,
+    pub panel_title: Color,
+    /// Content area of panels
+    pub panel_background: Color,
+    /// Toolbar and minibar
+    pub bar_background: Color,
+    pub menu_background: Color,
+    pub menu_item: Color,
+    pub menu_text: Color,
+    pub tooltip_border: Color,
+    pub tooltip_background: Color,
+    pub tooltip_text: Color,
+    pub dock_splitter: Color,
+    pub dock_tab_strip: Color,
+    pub dock_tab_active: Color,
+    pub dock_tab_inactive: Color,
+    /// Where a dragged panel would be docked
+    pub dock_preview: Color,
+    pub candidates_border: Color,
+    pub candidates_row: Color,
+    /// The candidate that would be selected on the next click
+    pub candidates_pending: Color,
+    /// Items in a list or grid, such as atlas frames
+    pub item_background: Color,
+    pub item_selected: Color,
+    /// Text inputs, number fields, checkboxes, dropdowns
+    pub field: Color,
+    pub field_focus: Color,
+    pub field_text: Color,
+    /// Selected text in text inputs
+    pub field_selection: Color,
+    pub slider_track: Color,
+    pub slider_fill: Color,
+}
+
+impl Default for ThemeColors {
+    fn default() -> Self {
+        ThemeColors {
+            text: Color::BLACK,
+            text_highlight: Color::PINK,
+            panel_border: Color::WHITE,
+            panel_titlebar: Color::BLACK,
+            panel_title: Color::WHITE,
+            panel_background: Color::rgb(0.75, 0.75, 0.75),
+            bar_background: Color::rgb(0.75, 0.75, 0.75),
+            menu_background: Color::WHITE,
+            menu_item: Color::BLACK,
+            menu_text: Color::WHITE,
+            tooltip_border: Color::BLACK,
+            tooltip_background: Color::BEIGE,
+            tooltip_text: Color::BLACK,
+            dock_splitter: Color::DARK_GRAY,
+            dock_tab_strip: Color::BLACK,
+            dock_tab_active: Color::rgb(0.75, 0.75, 0.75),
+            dock_tab_inactive: Color::rgb(0.5, 0.5, 0.5),
+            dock_preview: Color::rgba(0.3, 0.5, 1.0, 0.3),
+            candidates_border: Color::BLACK,
+            candidates_row: Color::BEIGE,
+            candidates_pending: Color::PINK,
+            item_background: Color::rgb(0.5, 0.5, 0.5),
+            item_selected: Color::WHITE,
+            field: Color::WHITE,
+            field_focus: Color::rgb(1.0, 1.0, 0.85),
+            field_text: Color::BLACK,
+            field_selection: Color::rgb(0.2, 0.4, 0.9),
+            slider_track: Color::rgb(0.55, 0.55, 0.55),
+            slider_fill: Color::rgb(0.45, 0.6, 0.9),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeFontSizes {
+    pub text: f32,
+    /// Panel titles, minibar buttons, submenu arrows
+    pub title: f32,
+    pub menu: f32,
+    pub tooltip_title: f32,
+    pub tooltip_text: f32,
+}
+
+impl Default for ThemeFontSizes {
+    fn default() -> Self {
+        ThemeFontSizes {
+            text: 12.0,
+            title: 16.0,
+            menu: 14.0,
+            tooltip_title: 16.0,
+            tooltip_text: 14.0,
+        }
+    }
+}
+
+/// Paddings, in pixels
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeSpacing {
+    pub panel_border: f32,
+    pub panel_titlebar: f32,
+    pub panel_contents: f32,
+    pub menu: f32,
+    pub menu_item: f32,
+    pub tooltip_border: f32,
+    pub tooltip: f32,
+    /// Left and right of the title in a dock tab
+    pub dock_tab: f32,
+    pub candidates: f32,
+}
+
+impl Default for ThemeSpacing {
+    fn default() -> Self {
+        ThemeSpacing {
+            panel_border: 2.0,
+            panel_titlebar: 2.0,
+            panel_contents: 4.0,
+            menu: 1.0,
+            menu_item: 2.0,
+            tooltip_border: 2.0,
+            tooltip: 4.0,
+            dock_tab: 4.0,
+            candidates: 2.0,
+        }
+    }
+}
+
+/// Global z-indices of the different parts of the UI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeLayers {
+    pub panels: i32,
+    pub dock: i32,
+    pub dock_preview: i32,
+    pub toolbar: i32,
+    pub minibar: i32,
+    pub menu: i32,
+    pub dropdown: i32,
+    pub candidates: i32,
+    pub tooltip: i32,
+}
+
+impl Default for ThemeLayers {
+    fn default() -> Self {
+        ThemeLayers {
+            panels: 9001,
+            dock: 9005,
+            dock_preview: 9008,
+            toolbar: 9010,
+            minibar: 9010,
+            menu: 9010,
+            dropdown: 9020,
+            candidates: 9050,
+            tooltip: 9100,
+        }
+    }
+}
+
+/// Set the `BackgroundColor` from the theme
+#[derive(Component, Clone, Copy)]
+pub struct ThemeBackground(pub fn(&EditorTheme) -> Color);
+
+/// Set the color and font size of all sections of the `Text` from the theme
+#[derive(Component, Clone, Copy)]
+pub struct ThemeText {
+    pub color: fn(&EditorTheme) -> Color,
+    pub size: fn(&EditorTheme) -> f32,
+}
+
+impl Default for ThemeText {
+    /// Regular text
+    fn default() -> Self {
+        ThemeText {
+            color: |t| t.colors.text,
+            size: |t| t.font_sizes.text,
+        }
+    }
+}
+
+/// Set the padding in the `Style` from the theme
+#[derive(Component, Clone, Copy)]
+pub struct ThemePadding(pub fn(&EditorTheme) -> UiRect);
+
+/// Set a global `ZIndex` from the theme
+#[derive(Component, Clone, Copy)]
+pub struct ThemeZIndex(pub fn(&EditorTheme) -> i32);
+
+#[derive(Default)]
+struct EditorThemeLoader;
+
+impl AssetLoader for EditorThemeLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let theme: EditorTheme = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(theme));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["theme.ron"]
+    }
+}
+
+fn init_theme(
+    mut commands: Commands,
+    assets: Res<EditorAssets>,
+    themes: Res<Assets<EditorTheme>>,
+) {
+    let theme = themes.get(&assets.theme).cloned().unwrap_or_default();
+    commands.insert_resource(theme);
+}
+
+fn theme_hot_reload(
+    mut evr: EventReader<AssetEvent<EditorTheme>>,
+    assets: Res<EditorAssets>,
+    themes: Res<Assets<EditorTheme>>,
+    mut theme: ResMut<EditorTheme>,
+) {
+    for ev in evr.iter() {
+        if let AssetEvent::Modified { handle } = ev {
+            if *handle != assets.theme {
+                continue;
+            }
+            if let Some(new) = themes.get(handle) {
+                info!("Reloaded editor theme");
+                *theme = new.clone();
+            }
+        }
+    }
+}
+
+/// Update everything if the theme changed, or else just the newly themed nodes
+fn apply_theme(
+    theme: Res<EditorTheme>,
+    mut q_background: Query<(Ref<ThemeBackground>, &mut BackgroundColor)>,
+    mut q_text: Query<(Ref<ThemeText>, &mut Text)>,
+    mut q_padding: Query<(Ref<ThemePadding>, &mut Style)>,
+    mut q_z: Query<(Ref<ThemeZIndex>, &mut ZIndex)>,
+) {
+    let all = theme.is_changed();
+    for (themed, mut color) in &mut q_background {
+        if all || themed.is_changed() {
+            color.0 = (themed.0)(&theme);
+        }
+    }
+    for (themed, mut text) in &mut q_text {
+        if all || themed.is_changed() {
+            let color = (themed.color)(&theme);
+            let size = (themed.size)(&theme);
+            for section in &mut text.sections {
+                section.style.color = color;
+                section.style.font_size = size;
+            }
+        }
+    }
+    for (themed, mut style) in &mut q_padding {
+        if all || themed.is_changed() {
+            style.padding = (themed.0)(&theme);
+        }
+    }
+    for (themed, mut z) in &mut q_z {
+        if all || themed.is_changed() {
+            *z = ZIndex::Global((themed.0)(&theme));
+        }
+    }
+}
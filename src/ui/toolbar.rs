@@ -1,4 +1,5 @@
 use crate::crate_prelude::*;
+use crate::ui::tooltip::TooltipText;
 
 pub(crate) struct ToolbarPlugin<S: States> {
     pub state: S,
@@ -6,6 +7,7 @@ pub(crate) struct ToolbarPlugin<S: States> {
 
 impl<S: States> Plugin for ToolbarPlugin<S> {
     fn build(&self, app: &mut App) {
+        app.init_resource::<ToolHotkeys>();
         app.add_systems(
             (
                 setup_toolbar,
@@ -14,6 +16,8 @@ impl<S: States> Plugin for ToolbarPlugin<S> {
         app.add_systems(
             (
                 toolbar_button_image,
+                toolbar_tooltips.run_if(resource_changed::<ToolHotkeys>()),
+                tool_hotkeys.run_if(crate::text::not_editing_text),
             ).in_set(EditorSet)
         );
     }
@@ -25,11 +29,11 @@ struct ToolbarTool(Tool);
 fn setup_toolbar(
     mut commands: Commands,
     assets: Res<EditorAssets>,
+    hotkeys: Res<ToolHotkeys>,
 ) {
     let toolbar = commands.spawn((
         NodeBundle {
             focus_policy: FocusPolicy::Pass,
-            z_index: ZIndex::Global(9010), // TODO: make this configurable
             style: Style {
                 position_type: PositionType::Absolute,
                 position: UiRect {
@@ -45,9 +49,10 @@ fn setup_toolbar(
                 flex_wrap: FlexWrap::Wrap,
                 ..Default::default()
             },
-            background_color: BackgroundColor(Color::rgb(0.75, 0.75, 0.75)),
             ..Default::default()
         },
+        ThemeBackground(|t| t.colors.bar_background),
+        ThemeZIndex(|t| t.layers.toolbar),
         EditorCleanup,
     )).id();
     // Logo Button
@@ -95,7 +100,7 @@ fn setup_toolbar(
             },
             ToolbarTool(tool),
             ClickBehavior::new().entity_system(toolbar_butt_handler),
-            tool.tooltip(),
+            tool.tooltip(&hotkeys),
         )).id();
         let icon = commands.spawn((
             ImageBundle {
@@ -131,3 +136,64 @@ fn toolbar_butt_handler(
         next_state.set(tool.0);
     }
 }
+
+/// Show the current hotkeys in the tooltips
+fn toolbar_tooltips(
+    hotkeys: Res<ToolHotkeys>,
+    mut q_button: Query<(&ToolbarTool, &mut TooltipText)>,
+) {
+    for (tool, mut tooltip) in &mut q_button {
+        *tooltip = tool.0.tooltip(&hotkeys);
+    }
+}
+
+/// A tool hotkey that is being held down
+struct HeldToolHotkey {
+    key: KeyCode,
+    tool: Tool,
+    /// To switch back to, if held long enough
+    previous: Tool,
+    since: Duration,
+}
+
+fn tool_hotkeys(
+    kbd: Res<Input<KeyCode>>,
+    time: Res<Time>,
+    hotkeys: Res<ToolHotkeys>,
+    current: Res<State<Tool>>,
+    mut next_tool: ResMut<NextState<Tool>>,
+    mut held: Local<Option<HeldToolHotkey>>,
+) {
+    // TODO: transition to a proper input mgmt framework like LWIM
+    if let Some(h) = &*held {
+        if kbd.pressed(h.key) {
+            return;
+        }
+        let long = time.elapsed() - h.since >= hotkeys.hold_duration;
+        // unless the tool was changed some other way in the meantime
+        if long && current.0 == h.tool {
+            next_tool.set(h.previous);
+        }
+        *held = None;
+    }
+    // leave key combos (copy/paste, etc.) to other shortcuts
+    if kbd.any_pressed([
+        KeyCode::LControl, KeyCode::RControl,
+        KeyCode::LAlt, KeyCode::RAlt,
+        KeyCode::LWin, KeyCode::RWin,
+    ]) {
+        return;
+    }
+    let Some((key, tool)) = kbd.get_just_pressed()
+        .find_map(|key| hotkeys.tool_for(*key).map(|tool| (*key, tool)))
+    else {
+        return;
+    };
+    next_tool.set(tool);
+    *held = Some(HeldToolHotkey {
+        key,
+        tool,
+        previous: current.0,
+        since: time.elapsed(),
+    });
+}
@@ -83,40 +83,43 @@ fn tooltip_spawner(
             if let Some(timer) = &mut timer.timer {
                 timer.tick(time.delta());
                 if timer.just_finished() {
+                    // `Pass`, so the tooltippable stays hovered; `Interaction`, so
+                    // that clicks on the tooltip don't go through to the world
                     let outer = commands.spawn((
                         NodeBundle {
                             focus_policy: FocusPolicy::Pass,
-                            z_index: ZIndex::Global(9100), // TODO: make this configurable
-                            background_color: BackgroundColor(Color::BLACK),
                             style: Style {
                                 position_type: PositionType::Absolute,
                                 position: compute_tooltip_position(query_windows.single()),
-                                padding: UiRect::all(Val::Px(2.0)),
                                 ..Default::default()
                             },
                             ..Default::default()
                         },
+                        ThemeBackground(|t| t.colors.tooltip_border),
+                        ThemePadding(|t| UiRect::all(Val::Px(t.spacing.tooltip_border))),
+                        ThemeZIndex(|t| t.layers.tooltip),
+                        Interaction::default(),
                         EditorCleanup,
                         TooltipDespawnTimer {
                             e_linked: e,
                             timer: Timer::new(Duration::from_millis(1000), TimerMode::Once),
                         },
                     )).id();
-                    let inner = commands.spawn(
+                    let inner = commands.spawn((
                         NodeBundle {
                             focus_policy: FocusPolicy::Pass,
-                            background_color: BackgroundColor(Color::BEIGE),
                             style: Style {
                                 flex_direction: FlexDirection::Column,
                                 align_items: AlignItems::FlexStart,
                                 justify_content: JustifyContent::FlexStart,
-                                padding: UiRect::all(Val::Px(4.0)),
                                 ..Default::default()
                             },
                             ..Default::default()
                         },
-                    ).id();
-                    let title = commands.spawn(
+                        ThemeBackground(|t| t.colors.tooltip_background),
+                        ThemePadding(|t| UiRect::all(Val::Px(t.spacing.tooltip))),
+                    )).id();
+                    let title = commands.spawn((
                         TextBundle {
                             style: Style {
                                 margin: UiRect {
@@ -127,22 +130,28 @@ fn tooltip_spawner(
                             },
                             text: Text::from_section(tooltip_text.title.clone(), TextStyle {
                                 font: assets.font_bold.clone(),
-                                font_size: 16.0,
-                                color: Color::BLACK,
+                                ..Default::default()
                             }),
                             ..Default::default()
-                        }
-                    ).id();
-                    let text = commands.spawn(
+                        },
+                        ThemeText {
+                            color: |t| t.colors.tooltip_text,
+                            size: |t| t.font_sizes.tooltip_title,
+                        },
+                    )).id();
+                    let text = commands.spawn((
                         TextBundle {
                             text: Text::from_section(tooltip_text.text.clone(), TextStyle {
                                 font: assets.font.clone(),
-                                font_size: 14.0,
-                                color: Color::BLACK,
+                                ..Default::default()
                             }),
                             ..Default::default()
-                        }
-                    ).id();
+                        },
+                        ThemeText {
+                            color: |t| t.colors.tooltip_text,
+                            size: |t| t.font_sizes.tooltip_text,
+                        },
+                    )).id();
                     commands.entity(outer).push_children(&[inner]);
                     commands.entity(inner).push_children(&[title, text]);
                 }
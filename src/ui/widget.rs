@@ -0,0 +1,1392 @@
+//! Reusable widgets for building editor panels
+//!
+//! Each widget is spawned as a child of a given parent entity (typically a
+//! panel's content area) using one of the `spawn_*` functions, and has a
+//! component holding its current value. Modifying that component updates
+//! what the widget shows. When the user changes the value, a
+//! `WidgetChanged<T>` event is sent, with the widget's entity and new value:
+//!
+//! - `spawn_text_input`: `TextInput`, `WidgetChanged<String>`
+//! - `spawn_number_field`: `NumberField`, `WidgetChanged<f32>`
+//!   (drag horizontally, or click to type a value)
+//! - `spawn_checkbox`: `Checkbox`, `WidgetChanged<bool>`
+//! - `spawn_slider`: `Slider`, `WidgetChanged<f32>`
+//! - `spawn_dropdown`: `Dropdown`, `WidgetChanged<usize>` (the selected option)
+//! - `spawn_color_picker`: `ColorPicker`, `WidgetChanged<Color>`
+//! - `spawn_vec2_field`/`spawn_vec3_field`: `VectorField`,
+//!   `WidgetChanged<Vec2>`/`WidgetChanged<Vec3>`
+//! - `spawn_section`: `Section`, `WidgetChanged<bool>` (whether it is open)
+//!
+//! Text inputs support selection (Shift+arrows, Ctrl+A) and the clipboard
+//! (Ctrl+C/X/V). While one is being edited, the editor's other keyboard
+//! shortcuts are disabled.
+
+use std::ops::Range;
+
+use bevy::input::mouse::MouseMotion;
+use bevy::window::PrimaryWindow;
+
+use crate::crate_prelude::*;
+use crate::clipboard::SystemClipboard;
+use crate::ui::{UiTextInputActive, UiTextInputOwner};
+
+use super::dock::cursor_ui;
+
+const FIELD_WIDTH: f32 = 120.0;
+const FIELD_HEIGHT: f32 = 16.0;
+/// Width of each component of a vector field
+const AXIS_WIDTH: f32 = 48.0;
+/// How far to drag a number field before it starts changing, in pixels
+const DRAG_THRESHOLD: f32 = 3.0;
+
+pub(crate) struct WidgetPlugin<S: States> {
+    pub state: S,
+}
+
+impl<S: States> Plugin for WidgetPlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WidgetFocus>();
+        app.init_resource::<WidgetClipboard>();
+        app.init_non_send_resource::<SystemClipboard>();
+        app.add_event::<WidgetChanged<String>>();
+        app.add_event::<WidgetChanged<f32>>();
+        app.add_event::<WidgetChanged<bool>>();
+        app.add_event::<WidgetChanged<usize>>();
+        app.add_event::<WidgetChanged<Color>>();
+        app.add_event::<WidgetChanged<Vec2>>();
+        app.add_event::<WidgetChanged<Vec3>>();
+        app.add_systems(
+            (
+                unfocus_widgets,
+            ).in_schedule(OnExit(self.state.clone()))
+        );
+        app.add_systems(
+            (
+                text_input_focus,
+                number_field_drag.after(text_input_focus),
+                text_input_keyboard.after(text_input_focus),
+                slider_drag,
+                dropdown_close,
+                color_picker_input
+                    .after(slider_drag),
+                vector_field_input
+                    .after(number_field_drag)
+                    .after(text_input_focus)
+                    .after(text_input_keyboard),
+            ).in_set(EditorSet)
+        );
+        app.add_systems(
+            (
+                color_picker_display
+                    .after(color_picker_input)
+                    .before(slider_display),
+                vector_field_display
+                    .after(vector_field_input)
+                    .before(number_field_display),
+                number_field_display
+                    .after(number_field_drag)
+                    .after(text_input_keyboard),
+                text_input_display
+                    .after(number_field_display),
+                slider_display
+                    .after(slider_drag),
+                checkbox_display,
+                dropdown_display,
+                section_display,
+            ).in_set(EditorSet)
+        );
+    }
+}
+
+/// Sent when the user changes the value of a widget
+#[derive(Debug, Clone)]
+pub struct WidgetChanged<T> {
+    pub entity: Entity,
+    pub value: T,
+}
+
+/// The text input currently being edited
+#[derive(Resource, Default)]
+struct WidgetFocus {
+    entity: Option<Entity>,
+    /// The value before editing, for Escape to revert to
+    original: String,
+}
+
+/// Clipboard for text inputs, if the system clipboard is not available
+#[derive(Resource, Default)]
+struct WidgetClipboard(String);
+
+fn clipboard_set(system: &mut SystemClipboard, fallback: &mut WidgetClipboard, text: &str) {
+    system.set_text(text);
+    fallback.0 = text.to_owned();
+}
+
+fn clipboard_get(system: &mut SystemClipboard, fallback: &WidgetClipboard) -> String {
+    system.get_text().unwrap_or_else(|| fallback.0.clone())
+}
+
+fn text_style(assets: &EditorAssets) -> TextStyle {
+    TextStyle {
+        font: assets.font.clone(),
+        ..Default::default()
+    }
+}
+
+/// Text inside of a widget
+fn field_text() -> ThemeText {
+    ThemeText {
+        color: |t| t.colors.field_text,
+        ..Default::default()
+    }
+}
+
+fn spawn_label(
+    commands: &mut Commands,
+    assets: &EditorAssets,
+    text: &str,
+) -> Entity {
+    commands.spawn((
+        TextBundle {
+            style: Style {
+                margin: UiRect::right(Val::Px(4.0)),
+                ..Default::default()
+            },
+            text: Text::from_section(text, text_style(assets)),
+            ..Default::default()
+        },
+        ThemeText::default(),
+    )).id()
+}
+
+/// Spawn a row with a label, to put a widget into
+///
+/// Returns the row entity; spawn the widget with it as the parent.
+pub fn spawn_widget_row(
+    commands: &mut Commands,
+    assets: &EditorAssets,
+    parent: Entity,
+    label: &str,
+) -> Entity {
+    let row = commands.spawn((
+        NodeBundle {
+            style: Style {
+                align_items: AlignItems::Center,
+                margin: UiRect::vertical(Val::Px(1.0)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )).id();
+    let label = spawn_label(commands, assets, label);
+    commands.entity(row).push_children(&[label]);
+    commands.entity(parent).push_children(&[row]);
+    row
+}
+
+// ---- Text Input ----
+
+/// Single-line text input
+#[derive(Component, Debug, Clone)]
+pub struct TextInput {
+    pub value: String,
+    /// Byte offset of the caret
+    cursor: usize,
+    /// Byte offset of the other end of the selection
+    anchor: usize,
+    text: Entity,
+}
+
+fn floor_boundary(s: &str, i: usize) -> usize {
+    let mut i = i.min(s.len());
+    while !s.is_char_boundary(i) {
+        i -= 1;
+    }
+    i
+}
+
+fn prev_boundary(s: &str, i: usize) -> usize {
+    s[..i].char_indices().next_back().map(|(i, _)| i).unwrap_or(0)
+}
+
+fn next_boundary(s: &str, i: usize) -> usize {
+    s[i..].chars().next().map(|c| i + c.len_utf8()).unwrap_or(i)
+}
+
+impl TextInput {
+    fn selection(&self) -> Range<usize> {
+        let cursor = floor_boundary(&self.value, self.cursor);
+        let anchor = floor_boundary(&self.value, self.anchor);
+        cursor.min(anchor)..cursor.max(anchor)
+    }
+
+    fn move_cursor(&mut self, to: usize, extend: bool) {
+        self.cursor = to;
+        if !extend {
+            self.anchor = to;
+        }
+    }
+
+    fn replace_selection(&mut self, s: &str) {
+        let range = self.selection();
+        let start = range.start;
+        self.value.replace_range(range, s);
+        self.move_cursor(start + s.len(), false);
+    }
+
+    /// The strings for the text sections: before, selected, caret, selected, after
+    fn display_sections(&self, focused: bool) -> [&str; 5] {
+        if !focused {
+            return [self.value.as_str(), "", "", "", ""];
+        }
+        let v = &self.value;
+        let cursor = floor_boundary(v, self.cursor);
+        let anchor = floor_boundary(v, self.anchor);
+        if cursor >= anchor {
+            [&v[..anchor], &v[anchor..cursor], "|", "", &v[cursor..]]
+        } else {
+            [&v[..cursor], "", "|", &v[cursor..anchor], &v[anchor..]]
+        }
+    }
+}
+
+/// Spawn a single-line text input
+pub fn spawn_text_input(
+    commands: &mut Commands,
+    assets: &EditorAssets,
+    parent: Entity,
+    value: &str,
+) -> Entity {
+    spawn_text_input_impl(commands, assets, parent, value, FIELD_WIDTH)
+}
+
+fn spawn_text_input_impl(
+    commands: &mut Commands,
+    assets: &EditorAssets,
+    parent: Entity,
+    value: &str,
+    width: f32,
+) -> Entity {
+    let text = commands.spawn((
+        TextBundle {
+            text: Text::from_sections([
+                TextSection::new(value, text_style(assets)),
+                TextSection::new("", text_style(assets)),
+                TextSection::new("", text_style(assets)),
+                TextSection::new("", text_style(assets)),
+                TextSection::new("", text_style(assets)),
+            ]),
+            ..Default::default()
+        },
+    )).id();
+    let input = commands.spawn((
+        NodeBundle {
+            focus_policy: FocusPolicy::Block,
+            style: Style {
+                size: Size::new(Val::Px(width), Val::Px(FIELD_HEIGHT)),
+                padding: UiRect::horizontal(Val::Px(2.0)),
+                align_items: AlignItems::Center,
+                overflow: Overflow::Hidden,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Interaction::default(),
+        TextInput {
+            value: value.to_owned(),
+            cursor: 0,
+            anchor: 0,
+            text,
+        },
+    )).id();
+    commands.entity(input).push_children(&[text]);
+    commands.entity(parent).push_children(&[input]);
+    input
+}
+
+/// Stop editing a text input; number fields parse and apply the typed value
+fn finish_editing(
+    e: Entity,
+    input: &mut TextInput,
+    number: Option<Mut<NumberField>>,
+    evw_number: &mut EventWriter<WidgetChanged<f32>>,
+) {
+    input.move_cursor(0, false);
+    if let Some(mut number) = number {
+        if let Ok(value) = input.value.trim().parse::<f32>() {
+            let value = number.clamp(value);
+            number.value = value;
+            evw_number.send(WidgetChanged { entity: e, value });
+        }
+        // show the value as it actually is
+        input.value = number.format();
+    }
+}
+
+fn unfocus_widgets(
+    mut focus: ResMut<WidgetFocus>,
+    mut text_input_active: ResMut<UiTextInputActive>,
+) {
+    if focus.entity.take().is_some() {
+        text_input_active.release(UiTextInputOwner::Widget);
+    }
+}
+
+fn text_input_focus(
+    mouse: Res<Input<MouseButton>>,
+    mut focus: ResMut<WidgetFocus>,
+    mut text_input_active: ResMut<UiTextInputActive>,
+    q_interaction: Query<(Entity, &Interaction), With<TextInput>>,
+    mut q_input: Query<(&mut TextInput, Option<&mut NumberField>)>,
+    mut evw_number: EventWriter<WidgetChanged<f32>>,
+    mut pressed: Local<Option<Entity>>,
+) {
+    // TODO: transition to a proper input mgmt framework like LWIM
+    if mouse.just_pressed(MouseButton::Left) {
+        *pressed = q_interaction.iter()
+            .find(|(_, interaction)| **interaction == Interaction::Clicked)
+            .map(|(e, _)| e);
+        // clicking anywhere else stops editing
+        if let Some(e) = focus.entity.filter(|e| Some(*e) != *pressed) {
+            focus.entity = None;
+            text_input_active.release(UiTextInputOwner::Widget);
+            if let Ok((mut input, number)) = q_input.get_mut(e) {
+                finish_editing(e, &mut input, number, &mut evw_number);
+            }
+        }
+    }
+    // focus on release, so that number fields can be dragged instead
+    if mouse.just_released(MouseButton::Left) {
+        let Some(e) = pressed.take() else {
+            return;
+        };
+        let Ok((mut input, number)) = q_input.get_mut(e) else {
+            return;
+        };
+        let dragged = number.map(|number| number.dragging).unwrap_or(false);
+        if !dragged && focus.entity != Some(e) {
+            focus.entity = Some(e);
+            focus.original = input.value.clone();
+            text_input_active.take(UiTextInputOwner::Widget);
+            // select everything, so typing replaces it
+            let end = input.value.len();
+            input.anchor = 0;
+            input.cursor = end;
+        }
+    }
+}
+
+fn text_input_keyboard(
+    kbd: Res<Input<KeyCode>>,
+    mut evr_char: EventReader<ReceivedCharacter>,
+    mut focus: ResMut<WidgetFocus>,
+    mut clipboard: ResMut<WidgetClipboard>,
+    mut system_clipboard: NonSendMut<SystemClipboard>,
+    mut text_input_active: ResMut<UiTextInputActive>,
+    mut q_input: Query<(&mut TextInput, Option<&mut NumberField>)>,
+    mut evw_string: EventWriter<WidgetChanged<String>>,
+    mut evw_number: EventWriter<WidgetChanged<f32>>,
+) {
+    // TODO: transition to a proper input mgmt framework like LWIM
+    let typed: String = evr_char.iter()
+        .map(|ev| ev.char)
+        .filter(|c| !c.is_control())
+        .collect();
+    let Some(e) = focus.entity else {
+        return;
+    };
+    let Ok((mut input, number)) = q_input.get_mut(e) else {
+        focus.entity = None;
+        text_input_active.release(UiTextInputOwner::Widget);
+        return;
+    };
+    // something else (such as a layer rename) took the text input
+    if !text_input_active.is_owner(UiTextInputOwner::Widget) {
+        focus.entity = None;
+        finish_editing(e, &mut input, number, &mut evw_number);
+        return;
+    }
+    if typed.is_empty() && kbd.get_just_pressed().next().is_none() {
+        return;
+    }
+
+    if kbd.just_pressed(KeyCode::Escape) {
+        input.value = focus.original.clone();
+        focus.entity = None;
+        text_input_active.release(UiTextInputOwner::Widget);
+        finish_editing(e, &mut input, number, &mut evw_number);
+        return;
+    }
+    if kbd.any_just_pressed([KeyCode::Return, KeyCode::NumpadEnter]) {
+        focus.entity = None;
+        text_input_active.release(UiTextInputOwner::Widget);
+        finish_editing(e, &mut input, number, &mut evw_number);
+        return;
+    }
+
+    let ctrl = kbd.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    let shift = kbd.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+    let old_value = input.value.clone();
+    // the value might have been changed by something else
+    let cursor = floor_boundary(&input.value, input.cursor);
+    input.cursor = cursor;
+    let anchor = floor_boundary(&input.value, input.anchor);
+    input.anchor = anchor;
+
+    if kbd.just_pressed(KeyCode::Left) {
+        let to = if !shift && cursor != anchor {
+            input.selection().start
+        } else {
+            prev_boundary(&input.value, cursor)
+        };
+        input.move_cursor(to, shift);
+    }
+    if kbd.just_pressed(KeyCode::Right) {
+        let to = if !shift && cursor != anchor {
+            input.selection().end
+        } else {
+            next_boundary(&input.value, cursor)
+        };
+        input.move_cursor(to, shift);
+    }
+    if kbd.just_pressed(KeyCode::Home) {
+        input.move_cursor(0, shift);
+    }
+    if kbd.just_pressed(KeyCode::End) {
+        let end = input.value.len();
+        input.move_cursor(end, shift);
+    }
+    if kbd.just_pressed(KeyCode::Back) {
+        if input.selection().is_empty() {
+            let to = prev_boundary(&input.value, input.cursor);
+            input.move_cursor(to, true);
+        }
+        input.replace_selection("");
+    }
+    if kbd.just_pressed(KeyCode::Delete) {
+        if input.selection().is_empty() {
+            let to = next_boundary(&input.value, input.cursor);
+            input.move_cursor(to, true);
+        }
+        input.replace_selection("");
+    }
+    if ctrl && kbd.just_pressed(KeyCode::A) {
+        let end = input.value.len();
+        input.anchor = 0;
+        input.cursor = end;
+    }
+    if ctrl && kbd.any_just_pressed([KeyCode::C, KeyCode::X]) {
+        let range = input.selection();
+        if !range.is_empty() {
+            clipboard_set(&mut system_clipboard, &mut clipboard, &input.value[range]);
+            if kbd.just_pressed(KeyCode::X) {
+                input.replace_selection("");
+            }
+        }
+    }
+    if ctrl && kbd.just_pressed(KeyCode::V) {
+        // single line only
+        let pasted = clipboard_get(&mut system_clipboard, &clipboard).replace(['\r', '\n'], " ");
+        input.replace_selection(&pasted);
+    }
+    if !typed.is_empty() && !ctrl {
+        input.replace_selection(&typed);
+    }
+
+    // number fields only report when done editing
+    if input.value != old_value && number.is_none() {
+        evw_string.send(WidgetChanged {
+            entity: e,
+            value: input.value.clone(),
+        });
+    }
+}
+
+/// Text inputs have a section per part of the text, so they are themed here
+fn text_input_display(
+    time: Res<Time>,
+    theme: Res<EditorTheme>,
+    focus: Res<WidgetFocus>,
+    mut q_input: Query<(Entity, &TextInput, &mut BackgroundColor)>,
+    mut q_text: Query<&mut Text>,
+) {
+    let blink = time.elapsed_seconds() % 1.0 < 0.5;
+    let colors = &theme.colors;
+    for (e, input, mut color) in &mut q_input {
+        let focused = focus.entity == Some(e);
+        let Ok(mut text) = q_text.get_mut(input.text) else {
+            continue;
+        };
+        let caret_color = if blink { colors.field_text } else { Color::NONE };
+        let section_colors = [
+            colors.field_text, colors.field_selection, caret_color, colors.field_selection, colors.field_text,
+        ];
+        // avoid triggering text layout every frame
+        for (i, s) in input.display_sections(focused).into_iter().enumerate() {
+            let section = &text.sections[i];
+            if section.value != s
+                || section.style.color != section_colors[i]
+                || section.style.font_size != theme.font_sizes.text
+            {
+                let section = &mut text.sections[i];
+                section.value = s.to_owned();
+                section.style.color = section_colors[i];
+                section.style.font_size = theme.font_sizes.text;
+            }
+        }
+        let bg = if focused { colors.field_focus } else { colors.field };
+        if color.0 != bg {
+            color.0 = bg;
+        }
+    }
+}
+
+// ---- Number Field ----
+
+/// Numeric field that can be dragged horizontally, or clicked to type into
+///
+/// The entity also has a `TextInput`.
+#[derive(Component, Debug, Clone)]
+pub struct NumberField {
+    pub value: f32,
+    pub min: f32,
+    pub max: f32,
+    /// How much the value changes per pixel of dragging
+    pub speed: f32,
+    /// Digits after the decimal point to show
+    pub precision: usize,
+    dragging: bool,
+    drag_distance: f32,
+}
+
+impl NumberField {
+    pub fn new(value: f32) -> Self {
+        NumberField {
+            value,
+            min: f32::NEG_INFINITY,
+            max: f32::INFINITY,
+            speed: 0.1,
+            precision: 2,
+            dragging: false,
+            drag_distance: 0.0,
+        }
+    }
+
+    pub fn with_range(mut self, min: f32, max: f32) -> Self {
+        self.min = min;
+        self.max = max;
+        self
+    }
+
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn with_precision(mut self, precision: usize) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    fn clamp(&self, value: f32) -> f32 {
+        value.max(self.min).min(self.max)
+    }
+
+    fn format(&self) -> String {
+        format!("{:.*}", self.precision, self.value)
+    }
+}
+
+/// Spawn a numeric field
+pub fn spawn_number_field(
+    commands: &mut Commands,
+    assets: &EditorAssets,
+    parent: Entity,
+    field: NumberField,
+) -> Entity {
+    spawn_number_field_impl(commands, assets, parent, field, FIELD_WIDTH)
+}
+
+fn spawn_number_field_impl(
+    commands: &mut Commands,
+    assets: &EditorAssets,
+    parent: Entity,
+    field: NumberField,
+    width: f32,
+) -> Entity {
+    let e = spawn_text_input_impl(commands, assets, parent, &field.format(), width);
+    commands.entity(e).insert(field);
+    e
+}
+
+fn number_field_drag(
+    mut motion: EventReader<MouseMotion>,
+    focus: Res<WidgetFocus>,
+    mut q_field: Query<(Entity, &Interaction, &mut NumberField)>,
+    mut evw: EventWriter<WidgetChanged<f32>>,
+) {
+    // TODO: transition to a proper input mgmt framework like LWIM
+    let delta: f32 = motion.iter().map(|ev| ev.delta.x).sum();
+    for (e, interaction, mut field) in &mut q_field {
+        if *interaction != Interaction::Clicked || focus.entity == Some(e) {
+            // (the focus system checks `dragging` on release, before this resets it)
+            if field.dragging || field.drag_distance != 0.0 {
+                field.dragging = false;
+                field.drag_distance = 0.0;
+            }
+            continue;
+        }
+        if delta == 0.0 {
+            continue;
+        }
+        field.drag_distance += delta.abs();
+        if field.drag_distance < DRAG_THRESHOLD {
+            continue;
+        }
+        field.dragging = true;
+        let value = field.clamp(field.value + delta * field.speed);
+        if value != field.value {
+            field.value = value;
+            evw.send(WidgetChanged { entity: e, value });
+        }
+    }
+}
+
+fn number_field_display(
+    focus: Res<WidgetFocus>,
+    mut q_field: Query<(Entity, &NumberField, &mut TextInput), Changed<NumberField>>,
+) {
+    for (e, field, mut input) in &mut q_field {
+        // don't interfere with typing
+        if focus.entity == Some(e) {
+            continue;
+        }
+        let s = field.format();
+        if input.value != s {
+            input.value = s;
+        }
+    }
+}
+
+// ---- Checkbox ----
+
+#[derive(Component, Debug, Clone)]
+pub struct Checkbox {
+    pub value: bool,
+}
+
+/// Spawn a checkbox
+pub fn spawn_checkbox(
+    commands: &mut Commands,
+    assets: &EditorAssets,
+    parent: Entity,
+    value: bool,
+) -> Entity {
+    let mark = commands.spawn((
+        TextBundle {
+            text: Text::from_section("", TextStyle {
+                font: assets.font_bold.clone(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        field_text(),
+    )).id();
+    let checkbox = commands.spawn((
+        ButtonBundle {
+            style: Style {
+                size: Size::new(Val::Px(FIELD_HEIGHT), Val::Px(FIELD_HEIGHT)),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Checkbox { value },
+        ThemeBackground(|t| t.colors.field),
+        ClickBehavior::new().entity_system(checkbox_click),
+    )).id();
+    commands.entity(checkbox).push_children(&[mark]);
+    commands.entity(parent).push_children(&[checkbox]);
+    checkbox
+}
+
+fn checkbox_click(
+    In(entity): In<Entity>,
+    mut q_checkbox: Query<&mut Checkbox>,
+    mut evw: EventWriter<WidgetChanged<bool>>,
+) {
+    let Ok(mut checkbox) = q_checkbox.get_mut(entity) else { return; };
+    checkbox.value = !checkbox.value;
+    evw.send(WidgetChanged {
+        entity,
+        value: checkbox.value,
+    });
+}
+
+fn checkbox_display(
+    q_checkbox: Query<(&Checkbox, &Children), Changed<Checkbox>>,
+    mut q_text: Query<&mut Text>,
+) {
+    for (checkbox, children) in &q_checkbox {
+        let mut iter = q_text.iter_many_mut(children.iter());
+        while let Some(mut text) = iter.fetch_next() {
+            text.sections[0].value = if checkbox.value { "x" } else { "" }.into();
+        }
+    }
+}
+
+// ---- Slider ----
+
+#[derive(Component, Debug, Clone)]
+pub struct Slider {
+    pub value: f32,
+    pub min: f32,
+    pub max: f32,
+    fill: Entity,
+    label: Entity,
+}
+
+impl Slider {
+    fn fraction(&self) -> f32 {
+        if self.max > self.min {
+            ((self.value - self.min) / (self.max - self.min)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Spawn a slider for a value between `min` and `max`
+pub fn spawn_slider(
+    commands: &mut Commands,
+    assets: &EditorAssets,
+    parent: Entity,
+    value: f32,
+    min: f32,
+    max: f32,
+) -> Entity {
+    let fill = commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(0.0),
+                    top: Val::Px(0.0),
+                    bottom: Val::Px(0.0),
+                    right: Val::Auto,
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        ThemeBackground(|t| t.colors.slider_fill),
+    )).id();
+    let label = commands.spawn((
+        TextBundle {
+            style: Style {
+                margin: UiRect::left(Val::Px(4.0)),
+                ..Default::default()
+            },
+            text: Text::from_section("", text_style(assets)),
+            ..Default::default()
+        },
+        field_text(),
+    )).id();
+    let slider = commands.spawn((
+        NodeBundle {
+            focus_policy: FocusPolicy::Block,
+            style: Style {
+                size: Size::new(Val::Px(FIELD_WIDTH), Val::Px(FIELD_HEIGHT)),
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Interaction::default(),
+        ThemeBackground(|t| t.colors.slider_track),
+        Slider {
+            value,
+            min,
+            max,
+            fill,
+            label,
+        },
+    )).id();
+    commands.entity(slider).push_children(&[fill, label]);
+    commands.entity(parent).push_children(&[slider]);
+    slider
+}
+
+fn slider_drag(
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    mut q_slider: Query<(Entity, &Interaction, &Node, &GlobalTransform, &mut Slider)>,
+    mut evw: EventWriter<WidgetChanged<f32>>,
+) {
+    let Some(cursor) = q_window.get_single().ok().and_then(cursor_ui) else {
+        return;
+    };
+    for (e, interaction, node, transform, mut slider) in &mut q_slider {
+        if *interaction != Interaction::Clicked || node.size().x <= 0.0 {
+            continue;
+        }
+        let left = transform.translation().x - node.size().x / 2.0;
+        let t = ((cursor.x - left) / node.size().x).clamp(0.0, 1.0);
+        let value = slider.min + t * (slider.max - slider.min);
+        if value != slider.value {
+            slider.value = value;
+            evw.send(WidgetChanged { entity: e, value });
+        }
+    }
+}
+
+fn slider_display(
+    q_slider: Query<&Slider, Changed<Slider>>,
+    mut q_style: Query<&mut Style>,
+    mut q_text: Query<&mut Text>,
+) {
+    for slider in &q_slider {
+        if let Ok(mut style) = q_style.get_mut(slider.fill) {
+            style.size.width = Val::Percent(slider.fraction() * 100.0);
+        }
+        if let Ok(mut text) = q_text.get_mut(slider.label) {
+            text.sections[0].value = format!("{:.2}", slider.value);
+        }
+    }
+}
+
+// ---- Dropdown ----
+
+#[derive(Component, Debug, Clone)]
+pub struct Dropdown {
+    pub options: Vec<String>,
+    /// Index into `options`
+    pub selected: usize,
+    label: Entity,
+    /// The list of options, while open
+    list: Option<Entity>,
+}
+
+/// The list of options of an open dropdown
+#[derive(Component)]
+struct DropdownList {
+    dropdown: Entity,
+}
+
+/// Button in an open dropdown list
+#[derive(Component)]
+struct DropdownOption {
+    dropdown: Entity,
+    index: usize,
+}
+
+/// Spawn a dropdown for choosing one of several options
+pub fn spawn_dropdown(
+    commands: &mut Commands,
+    assets: &EditorAssets,
+    parent: Entity,
+    options: Vec<String>,
+    selected: usize,
+) -> Entity {
+    let label = commands.spawn((
+        TextBundle {
+            text: Text::from_section("", text_style(assets)),
+            ..Default::default()
+        },
+        field_text(),
+    )).id();
+    let arrow = commands.spawn((
+        TextBundle {
+            text: Text::from_section("v", text_style(assets)),
+            ..Default::default()
+        },
+        field_text(),
+    )).id();
+    let dropdown = commands.spawn((
+        ButtonBundle {
+            style: Style {
+                size: Size::new(Val::Px(FIELD_WIDTH), Val::Px(FIELD_HEIGHT)),
+                padding: UiRect::horizontal(Val::Px(2.0)),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::SpaceBetween,
+                overflow: Overflow::Hidden,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Dropdown {
+            options,
+            selected,
+            label,
+            list: None,
+        },
+        ThemeBackground(|t| t.colors.field),
+        ClickBehavior::new().entity_system(dropdown_click),
+    )).id();
+    commands.entity(dropdown).push_children(&[label, arrow]);
+    commands.entity(parent).push_children(&[dropdown]);
+    dropdown
+}
+
+fn dropdown_click(
+    In(entity): In<Entity>,
+    mut commands: Commands,
+    assets: Res<EditorAssets>,
+    mut q_dropdown: Query<(&mut Dropdown, &Node, &GlobalTransform)>,
+) {
+    let Ok((mut dropdown, node, transform)) = q_dropdown.get_mut(entity) else { return; };
+    if let Some(list) = dropdown.list.take() {
+        commands.entity(list).despawn_recursive();
+        return;
+    }
+    // spawn at the top level, so it isn't clipped by the panel
+    let pos = transform.translation().truncate() + Vec2::new(-node.size().x, node.size().y) / 2.0;
+    let list = commands.spawn((
+        NodeBundle {
+            focus_policy: FocusPolicy::Block,
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(pos.x),
+                    top: Val::Px(pos.y),
+                    right: Val::Auto,
+                    bottom: Val::Auto,
+                },
+                min_size: Size::width(Val::Px(node.size().x)),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Stretch,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        DropdownList { dropdown: entity },
+        ThemeBackground(|t| t.colors.field),
+        ThemeZIndex(|t| t.layers.dropdown),
+        EditorCleanup,
+    )).id();
+    for (index, option) in dropdown.options.iter().enumerate() {
+        let text = commands.spawn((
+            TextBundle {
+                text: Text::from_section(option, text_style(&assets)),
+                ..Default::default()
+            },
+            field_text(),
+        )).id();
+        let butt = commands.spawn((
+            ButtonBundle {
+                style: Style {
+                    padding: UiRect::all(Val::Px(2.0)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            DropdownOption {
+                dropdown: entity,
+                index,
+            },
+            ThemeBackground(if index == dropdown.selected {
+                |t| t.colors.field_focus
+            } else {
+                |t| t.colors.field
+            }),
+            ClickBehavior::new().entity_system(dropdown_option_click),
+        )).id();
+        commands.entity(butt).push_children(&[text]);
+        commands.entity(list).push_children(&[butt]);
+    }
+    dropdown.list = Some(list);
+}
+
+fn dropdown_option_click(
+    In(entity): In<Entity>,
+    mut commands: Commands,
+    q_option: Query<&DropdownOption>,
+    mut q_dropdown: Query<&mut Dropdown>,
+    mut evw: EventWriter<WidgetChanged<usize>>,
+) {
+    let Ok(option) = q_option.get(entity) else { return; };
+    let Ok(mut dropdown) = q_dropdown.get_mut(option.dropdown) else { return; };
+    if let Some(list) = dropdown.list.take() {
+        commands.entity(list).despawn_recursive();
+    }
+    dropdown.selected = option.index;
+    evw.send(WidgetChanged {
+        entity: option.dropdown,
+        value: option.index,
+    });
+}
+
+/// Close open dropdowns when clicking elsewhere, or if the dropdown is gone
+fn dropdown_close(
+    mut commands: Commands,
+    mouse: Res<Input<MouseButton>>,
+    mut q_dropdown: Query<(&Interaction, &mut Dropdown)>,
+    q_option: Query<(&Interaction, &DropdownOption)>,
+    q_list: Query<(Entity, &DropdownList)>,
+) {
+    // TODO: transition to a proper input mgmt framework like LWIM
+    if mouse.just_pressed(MouseButton::Left) {
+        for (e_list, list) in &q_list {
+            let Ok((interaction, mut dropdown)) = q_dropdown.get_mut(list.dropdown) else {
+                continue;
+            };
+            let in_list = q_option.iter().any(|(interaction, option)| {
+                option.dropdown == list.dropdown && *interaction == Interaction::Clicked
+            });
+            // the dropdown itself toggles on click
+            if *interaction != Interaction::Clicked && !in_list {
+                commands.entity(e_list).despawn_recursive();
+                dropdown.list = None;
+            }
+        }
+    }
+    for (e_list, list) in &q_list {
+        if !q_dropdown.contains(list.dropdown) {
+            commands.entity(e_list).despawn_recursive();
+        }
+    }
+}
+
+fn dropdown_display(
+    q_dropdown: Query<&Dropdown, Changed<Dropdown>>,
+    mut q_text: Query<&mut Text>,
+) {
+    for dropdown in &q_dropdown {
+        let Ok(mut text) = q_text.get_mut(dropdown.label) else {
+            continue;
+        };
+        let s = dropdown.options.get(dropdown.selected).map(|s| s.as_str()).unwrap_or("");
+        if text.sections[0].value != s {
+            text.sections[0].value = s.into();
+        }
+    }
+}
+
+// ---- Color Picker ----
+
+#[derive(Component, Debug, Clone)]
+pub struct ColorPicker {
+    pub value: Color,
+    swatch: Entity,
+    /// Sliders for R, G, B, A
+    channels: [Entity; 4],
+}
+
+/// Marker for the sliders of a color picker
+#[derive(Component)]
+struct ColorPickerChannel {
+    picker: Entity,
+    index: usize,
+}
+
+/// Spawn a color picker, with a slider for each channel
+pub fn spawn_color_picker(
+    commands: &mut Commands,
+    assets: &EditorAssets,
+    parent: Entity,
+    value: Color,
+) -> Entity {
+    let picker = commands.spawn((
+        NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::FlexStart,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )).id();
+    let swatch = commands.spawn((
+        NodeBundle {
+            background_color: BackgroundColor(value),
+            style: Style {
+                size: Size::new(Val::Px(FIELD_WIDTH), Val::Px(FIELD_HEIGHT)),
+                margin: UiRect::bottom(Val::Px(1.0)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )).id();
+    commands.entity(picker).push_children(&[swatch]);
+    let rgba = value.as_rgba_f32();
+    let mut channels = [Entity::PLACEHOLDER; 4];
+    for (index, name) in ["R", "G", "B", "A"].into_iter().enumerate() {
+        let row = spawn_widget_row(commands, assets, picker, name);
+        channels[index] = spawn_slider(commands, assets, row, rgba[index], 0.0, 1.0);
+        commands.entity(channels[index]).insert(ColorPickerChannel { picker, index });
+    }
+    commands.entity(picker).insert(ColorPicker {
+        value,
+        swatch,
+        channels,
+    });
+    commands.entity(parent).push_children(&[picker]);
+    picker
+}
+
+fn color_picker_input(
+    mut evr: EventReader<WidgetChanged<f32>>,
+    q_channel: Query<&ColorPickerChannel>,
+    mut q_picker: Query<&mut ColorPicker>,
+    mut evw: EventWriter<WidgetChanged<Color>>,
+) {
+    for ev in evr.iter() {
+        let Ok(channel) = q_channel.get(ev.entity) else {
+            continue;
+        };
+        let Ok(mut picker) = q_picker.get_mut(channel.picker) else {
+            continue;
+        };
+        let mut rgba = picker.value.as_rgba_f32();
+        rgba[channel.index] = ev.value;
+        picker.value = Color::rgba(rgba[0], rgba[1], rgba[2], rgba[3]);
+        evw.send(WidgetChanged {
+            entity: channel.picker,
+            value: picker.value,
+        });
+    }
+}
+
+fn color_picker_display(
+    q_picker: Query<&ColorPicker, Changed<ColorPicker>>,
+    mut q_swatch: Query<&mut BackgroundColor>,
+    mut q_slider: Query<&mut Slider>,
+) {
+    for picker in &q_picker {
+        if let Ok(mut color) = q_swatch.get_mut(picker.swatch) {
+            color.0 = picker.value;
+        }
+        let rgba = picker.value.as_rgba_f32();
+        for (e, value) in picker.channels.iter().zip(rgba) {
+            if let Ok(mut slider) = q_slider.get_mut(*e) {
+                if slider.value != value {
+                    slider.value = value;
+                }
+            }
+        }
+    }
+}
+
+// ---- Vector Field ----
+
+/// A number field for each component of a vector
+#[derive(Component, Debug, Clone)]
+pub struct VectorField {
+    /// Only the first `dims` components are used
+    pub value: Vec4,
+    dims: usize,
+    axes: Vec<Entity>,
+}
+
+/// Marker for the number fields of a vector field
+#[derive(Component)]
+struct VectorFieldAxis {
+    field: Entity,
+    axis: usize,
+}
+
+/// Spawn a field for editing a `Vec2`
+pub fn spawn_vec2_field(
+    commands: &mut Commands,
+    assets: &EditorAssets,
+    parent: Entity,
+    value: Vec2,
+) -> Entity {
+    spawn_vector_field(commands, assets, parent, value.extend(0.0).extend(0.0), 2)
+}
+
+/// Spawn a field for editing a `Vec3`
+pub fn spawn_vec3_field(
+    commands: &mut Commands,
+    assets: &EditorAssets,
+    parent: Entity,
+    value: Vec3,
+) -> Entity {
+    spawn_vector_field(commands, assets, parent, value.extend(0.0), 3)
+}
+
+fn spawn_vector_field(
+    commands: &mut Commands,
+    assets: &EditorAssets,
+    parent: Entity,
+    value: Vec4,
+    dims: usize,
+) -> Entity {
+    let field = commands.spawn((
+        NodeBundle {
+            style: Style {
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )).id();
+    let mut axes = vec![];
+    for (axis, name) in ["X", "Y", "Z", "W"].into_iter().enumerate().take(dims) {
+        let label = spawn_label(commands, assets, name);
+        commands.entity(field).push_children(&[label]);
+        let e = spawn_number_field_impl(commands, assets, field, NumberField::new(value[axis]), AXIS_WIDTH);
+        commands.entity(e).insert(VectorFieldAxis { field, axis });
+        axes.push(e);
+    }
+    commands.entity(field).insert(VectorField {
+        value,
+        dims,
+        axes,
+    });
+    commands.entity(parent).push_children(&[field]);
+    field
+}
+
+fn vector_field_input(
+    mut evr: EventReader<WidgetChanged<f32>>,
+    q_axis: Query<&VectorFieldAxis>,
+    mut q_field: Query<&mut VectorField>,
+    mut evw_vec2: EventWriter<WidgetChanged<Vec2>>,
+    mut evw_vec3: EventWriter<WidgetChanged<Vec3>>,
+) {
+    for ev in evr.iter() {
+        let Ok(axis) = q_axis.get(ev.entity) else {
+            continue;
+        };
+        let Ok(mut field) = q_field.get_mut(axis.field) else {
+            continue;
+        };
+        field.value[axis.axis] = ev.value;
+        match field.dims {
+            2 => evw_vec2.send(WidgetChanged {
+                entity: axis.field,
+                value: field.value.truncate().truncate(),
+            }),
+            _ => evw_vec3.send(WidgetChanged {
+                entity: axis.field,
+                value: field.value.truncate(),
+            }),
+        }
+    }
+}
+
+fn vector_field_display(
+    q_field: Query<&VectorField, Changed<VectorField>>,
+    mut q_number: Query<&mut NumberField>,
+) {
+    for field in &q_field {
+        for (axis, e) in field.axes.iter().enumerate() {
+            if let Ok(mut number) = q_number.get_mut(*e) {
+                if number.value != field.value[axis] {
+                    number.value = field.value[axis];
+                }
+            }
+        }
+    }
+}
+
+// ---- Collapsible Section ----
+
+#[derive(Component, Debug, Clone)]
+pub struct Section {
+    pub open: bool,
+    title: String,
+    header_text: Entity,
+    body: Entity,
+}
+
+/// Spawn a collapsible section with a clickable header
+///
+/// Returns (section_entity, body_entity). Put the contents into the body.
+pub fn spawn_section(
+    commands: &mut Commands,
+    assets: &EditorAssets,
+    parent: Entity,
+    title: &str,
+    open: bool,
+) -> (Entity, Entity) {
+    let section = commands.spawn((
+        NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Stretch,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )).id();
+    let header_text = commands.spawn((
+        TextBundle {
+            text: Text::from_section("", TextStyle {
+                font: assets.font_bold.clone(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        ThemeText::default(),
+    )).id();
+    let header = commands.spawn((
+        ButtonBundle {
+            background_color: BackgroundColor(Color::NONE),
+            style: Style {
+                padding: UiRect::vertical(Val::Px(1.0)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        ClickBehavior::new().entity_system(section_click),
+    )).id();
+    let body = commands.spawn((
+        NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::FlexStart,
+                padding: UiRect::left(Val::Px(8.0)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )).id();
+    commands.entity(header).push_children(&[header_text]);
+    commands.entity(section).push_children(&[header, body]);
+    commands.entity(section).insert(Section {
+        open,
+        title: title.into(),
+        header_text,
+        body,
+    });
+    commands.entity(header).insert(SectionHeader { section });
+    commands.entity(parent).push_children(&[section]);
+    (section, body)
+}
+
+#[derive(Component)]
+struct SectionHeader {
+    section: Entity,
+}
+
+fn section_click(
+    In(entity): In<Entity>,
+    q_header: Query<&SectionHeader>,
+    mut q_section: Query<&mut Section>,
+    mut evw: EventWriter<WidgetChanged<bool>>,
+) {
+    let Ok(header) = q_header.get(entity) else { return; };
+    let Ok(mut section) = q_section.get_mut(header.section) else { return; };
+    section.open = !section.open;
+    evw.send(WidgetChanged {
+        entity: header.section,
+        value: section.open,
+    });
+}
+
+fn section_display(
+    q_section: Query<&Section, Changed<Section>>,
+    mut q_style: Query<&mut Style>,
+    mut q_text: Query<&mut Text>,
+) {
+    for section in &q_section {
+        if let Ok(mut style) = q_style.get_mut(section.body) {
+            style.display = if section.open { Display::Flex } else { Display::None };
+        }
+        if let Ok(mut text) = q_text.get_mut(section.header_text) {
+            text.sections[0].value = format!("[{}] {}", if section.open { "-" } else { "+" }, section.title);
+        }
+    }
+}